@@ -17,9 +17,9 @@ use bevy::{
     sprite_render::Material2dPlugin,
 };
 use materials::{
-    SpineAdditiveMaterial, SpineAdditivePmaMaterial, SpineMaterialInfo, SpineMultiplyMaterial,
-    SpineMultiplyPmaMaterial, SpineNormalMaterial, SpineNormalPmaMaterial, SpineScreenMaterial,
-    SpineScreenPmaMaterial,
+    SpineAdditiveMaterial, SpineAdditivePmaMaterial, SpineEffectMaterial, SpineEffectPmaMaterial,
+    SpineMaterialInfo, SpineMultiplyMaterial, SpineMultiplyPmaMaterial, SpineNormalMaterial,
+    SpineNormalPmaMaterial, SpineScreenMaterial, SpineScreenPmaMaterial,
 };
 use rusty_spine::{
     atlas::{AtlasFilter, AtlasWrap},
@@ -30,7 +30,7 @@ use textures::SpineTextureConfig;
 
 use crate::{
     assets::{AtlasLoader, SkeletonJsonLoader},
-    materials::{SpineMaterialPlugin, DARK_COLOR_ATTRIBUTE, SHADER_HANDLE},
+    materials::{SpineMaterialPlugin, DARK_COLOR_ATTRIBUTE, EFFECT_SHADER_HANDLE, SHADER_HANDLE},
     rusty_spine::{
         controller::SkeletonControllerSettings, draw::CullDirection, AnimationStateData, BoneHandle,
     },
@@ -108,6 +108,8 @@ impl Plugin for SpinePlugin {
             Material2dPlugin::<SpineAdditivePmaMaterial>::default(),
             Material2dPlugin::<SpineMultiplyPmaMaterial>::default(),
             Material2dPlugin::<SpineScreenPmaMaterial>::default(),
+            Material2dPlugin::<SpineEffectMaterial>::default(),
+            Material2dPlugin::<SpineEffectPmaMaterial>::default(),
         ))
         .add_plugins((
             SpineMaterialPlugin::<SpineNormalMaterial>::default(),
@@ -118,6 +120,8 @@ impl Plugin for SpinePlugin {
             SpineMaterialPlugin::<SpineAdditivePmaMaterial>::default(),
             SpineMaterialPlugin::<SpineMultiplyPmaMaterial>::default(),
             SpineMaterialPlugin::<SpineScreenPmaMaterial>::default(),
+            SpineMaterialPlugin::<SpineEffectMaterial>::default(),
+            SpineMaterialPlugin::<SpineEffectPmaMaterial>::default(),
         ))
         .add_plugins(SpineSyncPlugin::first())
         .init_resource::<SpineMsgQueue>()
@@ -174,6 +178,15 @@ impl Plugin for SpinePlugin {
                 path
             )
         );
+        load_internal_binary_asset!(
+            app,
+            EFFECT_SHADER_HANDLE,
+            "spine_effect.wgsl",
+            |bytes: &[u8], path: String| Shader::from_wgsl(
+                std::str::from_utf8(bytes).unwrap().to_owned(),
+                path
+            )
+        );
     }
 }
 
@@ -359,6 +372,13 @@ impl Default for SpineSettings {
     }
 }
 
+/// Skips the automatic [`SkeletonController`] update of this Spine.
+///
+/// For skeletons stepped by hand with a fixed delta, such as frames rendered offline. Events
+/// fired by those updates are still sent as [`SpineMsg`]s.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct SpineManualUpdate;
+
 /// An [`Message`] which is sent once a [`SpineLoader`] has fully loaded a skeleton and attached the
 /// [`Spine`] component.
 ///
@@ -746,7 +766,7 @@ fn spine_ready(
 }
 
 fn spine_update_animation(
-    mut spine_query: Query<(Entity, &mut Spine)>,
+    mut spine_query: Query<(Entity, &mut Spine), Without<SpineManualUpdate>>,
     mut spine_events: MessageWriter<SpineMsg>,
     time: Res<Time>,
     spine_event_queue: Res<SpineMsgQueue>,
@@ -1061,15 +1081,22 @@ mod crossfades;
 mod entity_sync;
 mod handle;
 
+#[cfg(feature = "audio")]
+pub mod audio;
+pub mod debug;
 pub mod materials;
 pub mod textures;
 
 #[doc(hidden)]
 pub mod prelude {
     pub use crate::{
-        Crossfades, SkeletonController, SkeletonData, SkeletonDataHandle, Spine, SpineBone,
-        SpineMsg, SpineLoader, SpineMesh, SpineMeshState, SpinePlugin, SpineReadyMsg, SpineSet,
-        SpineSettings, SpineSync, SpineSyncSet, SpineSyncSystem, SpineSystem,
+        debug::{DebugBone, DebugConstraint, DebugConstraintKind, DebugSlot, SkeletonDebug},
+        materials::{SpineImageEffect, SpineRuleFade}, Crossfades, SkeletonController, SkeletonData, SkeletonDataHandle, Spine, SpineBone,
+        SpineManualUpdate, SpineMsg, SpineLoader, SpineMesh, SpineMeshState, SpinePlugin, SpineReadyMsg,
+        SpineSet, SpineSettings, SpineSync, SpineSyncSet, SpineSyncSystem, SpineSystem,
     };
     pub use rusty_spine::{BoneHandle, SlotHandle};
+
+    #[cfg(feature = "audio")]
+    pub use crate::audio::{SpineAudioPlugin, SpineAudioSettings, SpineEventSound};
 }
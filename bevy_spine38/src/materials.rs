@@ -1,6 +1,8 @@
 //! Materials for Spine meshes.
 //!
-//! To create a custom material for Spine, see [`SpineMaterial`].
+//! To create a custom material for Spine, see [`SpineMaterial`]. Image effects such as grayscale
+//! or blur can be applied to a skeleton with [`SpineImageEffect`], and a rule transition with
+//! [`SpineRuleFade`].
 
 use std::marker::PhantomData;
 
@@ -12,7 +14,7 @@ use bevy::{
     reflect::TypePath,
     render::render_resource::{
         AsBindGroup, BlendComponent, BlendFactor, BlendOperation, BlendState,
-        RenderPipelineDescriptor, SpecializedMeshPipelineError, VertexFormat,
+        RenderPipelineDescriptor, ShaderType, SpecializedMeshPipelineError, VertexFormat,
     },
     shader::ShaderRef,
     sprite_render::{AlphaMode2d, Material2d, Material2dKey},
@@ -142,6 +144,8 @@ pub const DARK_COLOR_ATTRIBUTE: MeshVertexAttribute = MeshVertexAttribute::new(
     VertexFormat::Float32x4,
 );
 
+pub const EFFECT_SHADER_HANDLE: Handle<Shader> = uuid_handle!("00000000-0000-0000-6c1e-8f3a0d9b2e57");
+
 pub const SHADER_HANDLE: Handle<Shader> = uuid_handle!("00000000-0000-0000-93e0-1b9409bb3c11");
 
 /// A [`SystemParam`] to query [`SpineSettings`].
@@ -150,6 +154,74 @@ pub const SHADER_HANDLE: Handle<Shader> = uuid_handle!("00000000-0000-0000-93e0-
 #[derive(SystemParam)]
 pub struct SpineSettingsQuery<'w, 's> {
     pub spine_settings_query: Query<'w, 's, &'static SpineSettings>,
+    pub effect_query: Query<'w, 's, &'static SpineImageEffect>,
+    pub rule_query: Query<'w, 's, &'static SpineRuleFade>,
+}
+
+/// Image effects applied to every slot of a [`Spine`](crate::Spine) using the normal blend mode.
+///
+/// While this component exists, the normal blend mode slots are drawn with
+/// [`SpineEffectMaterial`] or [`SpineEffectPmaMaterial`] instead of the default materials. Remove
+/// it to go back to the default materials.
+#[derive(Component, ShaderType, Debug, Default, Clone, Copy, PartialEq)]
+pub struct SpineImageEffect {
+    /// Grayscale strength, `0.` to `1.`.
+    pub grayscale: f32,
+    /// Sepia strength, `0.` to `1.`.
+    pub sepia: f32,
+    /// Negative strength, `0.` to `1.`.
+    pub negative: f32,
+    /// Blur radius in texels.
+    pub blur: f32,
+    /// Mosaic block size in texels, values up to `1.` are disabled.
+    pub mosaic: f32,
+}
+
+/// Rule transition applied to every slot of a [`Spine`](crate::Spine) using the normal blend mode.
+///
+/// The rule image is stretched over `rect` and slots are revealed where its red channel is below
+/// `progress`, so dark pixels appear first. Like [`SpineImageEffect`], the normal blend mode slots
+/// are drawn with [`SpineEffectMaterial`] or [`SpineEffectPmaMaterial`] while this component exists.
+#[derive(Component, Debug, Default, Clone, PartialEq)]
+pub struct SpineRuleFade {
+    /// Grayscale rule image.
+    pub rule: Handle<Image>,
+    /// World space rectangle covered by the rule image.
+    pub rect: Rect,
+    /// `0.` hides the slots, `1.` shows them fully.
+    pub progress: f32,
+    /// Width of the soft edge in rule values, `0.` gives a hard edge.
+    pub softness: f32,
+}
+
+/// Uniform of [`SpineRuleFade`] in the effect materials.
+#[derive(ShaderType, Debug, Clone, Copy, PartialEq)]
+pub struct SpineRuleParams {
+    /// Minimum corner and size of [`SpineRuleFade::rect`].
+    pub rect: Vec4,
+    pub progress: f32,
+    pub softness: f32,
+}
+
+impl Default for SpineRuleParams {
+    // no transition, every rule value is revealed
+    fn default() -> Self {
+        Self {
+            rect: Vec4::new(0., 0., 1., 1.),
+            progress: 1.,
+            softness: 1.,
+        }
+    }
+}
+
+impl From<&SpineRuleFade> for SpineRuleParams {
+    fn from(fade: &SpineRuleFade) -> Self {
+        Self {
+            rect: Vec4::new(fade.rect.min.x, fade.rect.min.y, fade.rect.width(), fade.rect.height()),
+            progress: fade.progress,
+            softness: fade.softness,
+        }
+    }
 }
 
 macro_rules! material {
@@ -217,7 +289,9 @@ macro_rules! material {
                 params: &StaticSystemParam<Self::Params<'_, '_>>,
             ) -> Option<Self> {
                 let spine_settings = params.spine_settings_query.get(entity).copied().unwrap_or(SpineSettings::default());
-                if spine_settings.default_materials && renderable_data.blend_mode == $blend_mode && renderable_data.premultiplied_alpha == $premultiplied_alpha {
+                let effect = renderable_data.blend_mode == BlendMode::Normal
+                    && (params.effect_query.contains(entity) || params.rule_query.contains(entity));
+                if spine_settings.default_materials && !effect && renderable_data.blend_mode == $blend_mode && renderable_data.premultiplied_alpha == $premultiplied_alpha {
                     let mut material = material.unwrap_or_else(|| Self::default());
                     material.image = renderable_data.texture;
                     Some(material)
@@ -380,3 +454,128 @@ material!(
         },
     }
 );
+
+macro_rules! effect_material {
+    ($(#[$($attrss:tt)*])* $name:ident, $premultiplied_alpha:expr, $blend_state:expr) => {
+        $(#[$($attrss)*])*
+        #[derive(Asset, Default, AsBindGroup, TypePath, Clone)]
+        pub struct $name {
+            #[texture(0)]
+            #[sampler(1)]
+            pub image: Handle<Image>,
+            #[uniform(2)]
+            pub effect: SpineImageEffect,
+            #[uniform(3)]
+            pub rule_params: SpineRuleParams,
+            #[texture(4)]
+            #[sampler(5)]
+            pub rule: Option<Handle<Image>>,
+        }
+
+        impl Material2d for $name {
+            fn vertex_shader() -> ShaderRef {
+                SHADER_HANDLE.into()
+            }
+
+            fn fragment_shader() -> ShaderRef {
+                EFFECT_SHADER_HANDLE.into()
+            }
+
+            fn alpha_mode(&self) -> AlphaMode2d {
+                AlphaMode2d::Blend
+            }
+
+            fn specialize(
+                descriptor: &mut RenderPipelineDescriptor,
+                layout: &MeshVertexBufferLayoutRef,
+                _key: Material2dKey<Self>,
+            ) -> Result<(), SpecializedMeshPipelineError> {
+                let vertex_attributes = vec![
+                    Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
+                    Mesh::ATTRIBUTE_NORMAL.at_shader_location(1),
+                    Mesh::ATTRIBUTE_UV_0.at_shader_location(2),
+                    Mesh::ATTRIBUTE_COLOR.at_shader_location(4),
+                    DARK_COLOR_ATTRIBUTE.at_shader_location(DARK_COLOR_SHADER_POSITION as u32),
+                ];
+                let vertex_buffer_layout = layout.0.get_layout(&vertex_attributes)?;
+                descriptor.vertex.buffers = vec![vertex_buffer_layout];
+                if let Some(fragment) = &mut descriptor.fragment {
+                    if $premultiplied_alpha {
+                        fragment.shader_defs.push("PREMULTIPLIED_ALPHA".into());
+                    }
+                    if let Some(target_state) = &mut fragment.targets[0] {
+                        target_state.blend = Some($blend_state);
+                    }
+                }
+                descriptor.primitive.cull_mode = None;
+                Ok(())
+            }
+        }
+
+        impl SpineMaterial for $name {
+            type MeshMaterial = MeshMaterial2d<Self>;
+            type Material = Self;
+            type Params<'w, 's> = SpineSettingsQuery<'w, 's>;
+
+            fn update(
+                material: Option<Self>,
+                entity: Entity,
+                renderable_data: SpineMaterialInfo,
+                params: &StaticSystemParam<Self::Params<'_, '_>>,
+            ) -> Option<Self> {
+                let spine_settings = params.spine_settings_query.get(entity).copied().unwrap_or(SpineSettings::default());
+                let effect = params.effect_query.get(entity).ok();
+                let rule = params.rule_query.get(entity).ok();
+                if effect.is_none() && rule.is_none() {
+                    return None;
+                }
+                if spine_settings.default_materials && renderable_data.blend_mode == BlendMode::Normal && renderable_data.premultiplied_alpha == $premultiplied_alpha {
+                    let mut material = material.unwrap_or_else(|| Self::default());
+                    material.image = renderable_data.texture;
+                    material.effect = effect.copied().unwrap_or_default();
+                    material.rule_params = rule.map(SpineRuleParams::from).unwrap_or_default();
+                    material.rule = rule.map(|rule| rule.rule.clone());
+                    Some(material)
+                } else {
+                    None
+                }
+            }
+        }
+    };
+}
+
+effect_material!(
+    /// Normal blend mode material with [`SpineImageEffect`] and [`SpineRuleFade`], non-premultiplied-alpha
+    SpineEffectMaterial,
+    false,
+    BlendState {
+        color: BlendComponent {
+            src_factor: BlendFactor::SrcAlpha,
+            dst_factor: BlendFactor::OneMinusSrcAlpha,
+            operation: BlendOperation::Add,
+        },
+        alpha: BlendComponent {
+            src_factor: BlendFactor::One,
+            dst_factor: BlendFactor::OneMinusSrcAlpha,
+            operation: BlendOperation::Add,
+        },
+    }
+);
+
+effect_material!(
+    /// Normal blend mode material with [`SpineImageEffect`] and [`SpineRuleFade`], premultiplied-alpha
+    SpineEffectPmaMaterial,
+    true,
+    BlendState {
+        color: BlendComponent {
+            src_factor: BlendFactor::One,
+            dst_factor: BlendFactor::OneMinusSrcAlpha,
+            operation: BlendOperation::Add,
+        },
+        alpha: BlendComponent {
+            src_factor: BlendFactor::One,
+            dst_factor: BlendFactor::OneMinusSrcAlpha,
+            operation: BlendOperation::Add,
+        },
+    }
+);
@@ -0,0 +1,223 @@
+use std::path::Path;
+
+use bevy::{
+    audio::{AddAudioSource, AudioSource, Decodable, Sample, Source, Volume},
+    prelude::*,
+};
+
+use crate::{SkeletonData, SkeletonDataHandle, SpineMsg, SpineSet};
+
+type SourceDecoder = <AudioSource as Decodable>::Decoder;
+type SourceSample = <SourceDecoder as Iterator>::Item;
+
+/// Plays the audio attached to [`SpineMsg::Event`]s.
+///
+/// The event's `audio_path` is loaded relative to the folder of the skeleton's atlas, and played
+/// with the event `volume` and a stereo pan derived from `balance`. Requires the `audio` feature.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_spine::prelude::*;
+/// # fn doc(app: &mut App) {
+/// app.add_plugins((SpinePlugin, SpineAudioPlugin));
+/// # }
+/// ```
+pub struct SpineAudioPlugin;
+
+impl Plugin for SpineAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_source::<SpineEventAudio>()
+            .init_resource::<SpineAudioSettings>()
+            .add_systems(
+                Update,
+                (
+                    spine_event_audio.in_set(SpineSet::OnEvent),
+                    spine_event_audio_ready.after(SpineSet::OnEvent),
+                ),
+            );
+    }
+}
+
+/// Global settings for audio played by [`SpineAudioPlugin`].
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct SpineAudioSettings {
+    /// Multiplied with the event volume when a sound starts.
+    pub volume: f32,
+}
+
+impl Default for SpineAudioSettings {
+    fn default() -> Self {
+        Self { volume: 1. }
+    }
+}
+
+/// Added to entities playing Spine event audio.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct SpineEventSound {
+    /// The [`Spine`](`crate::Spine`) entity which fired the event.
+    pub spine: Entity,
+}
+
+/// Waits for the audio file to load before it can be wrapped in a [`SpineEventAudio`].
+#[derive(Component)]
+struct SpineEventSoundLoading {
+    source: Handle<AudioSource>,
+    volume: f32,
+    balance: f32,
+    spine: Entity,
+}
+
+/// An [`AudioSource`] panned to the left (-1) or right (1).
+#[derive(Asset, TypePath)]
+pub struct SpineEventAudio {
+    source: AudioSource,
+    balance: f32,
+}
+
+impl Decodable for SpineEventAudio {
+    type DecoderItem = SourceSample;
+    type Decoder = BalanceDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        let decoder = self.source.decoder();
+        let balance = self.balance.clamp(-1., 1.);
+        BalanceDecoder {
+            channels: decoder.channels(),
+            decoder,
+            gains: [(1. - balance).min(1.), (1. + balance).min(1.)],
+            channel: 0,
+            mono_sample: None,
+        }
+    }
+}
+
+/// Applies left/right gains to a decoded stream, mono streams are played as stereo.
+pub struct BalanceDecoder {
+    decoder: SourceDecoder,
+    gains: [f32; 2],
+    channels: u16,
+    channel: usize,
+    mono_sample: Option<SourceSample>,
+}
+
+impl Iterator for BalanceDecoder {
+    type Item = SourceSample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.channels {
+            1 => {
+                if let Some(sample) = self.mono_sample.take() {
+                    return Some(sample.amplify(self.gains[1]));
+                }
+                let sample = self.decoder.next()?;
+                self.mono_sample = Some(sample);
+                Some(sample.amplify(self.gains[0]))
+            }
+            2 => {
+                let sample = self.decoder.next()?;
+                let gain = self.gains[self.channel];
+                self.channel = 1 - self.channel;
+                Some(sample.amplify(gain))
+            }
+            _ => self.decoder.next(),
+        }
+    }
+}
+
+impl Source for BalanceDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        let len = self.decoder.current_frame_len();
+        if self.channels == 1 {
+            len.map(|len| len * 2)
+        } else {
+            len
+        }
+    }
+
+    fn channels(&self) -> u16 {
+        if self.channels == 1 {
+            2
+        } else {
+            self.channels
+        }
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.decoder.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<std::time::Duration> {
+        self.decoder.total_duration()
+    }
+}
+
+fn spine_event_audio(
+    mut commands: Commands,
+    mut spine_msgs: MessageReader<SpineMsg>,
+    spine_query: Query<&SkeletonDataHandle>,
+    skeletons: Res<Assets<SkeletonData>>,
+    asset_server: Res<AssetServer>,
+) {
+    for msg in spine_msgs.read() {
+        let SpineMsg::Event {
+            entity,
+            audio_path,
+            volume,
+            balance,
+            ..
+        } = msg
+        else {
+            continue;
+        };
+        if audio_path.is_empty() {
+            continue;
+        }
+        let Some(folder) = spine_query
+            .get(*entity)
+            .ok()
+            .and_then(|handle| skeletons.get(&handle.0))
+            .and_then(|skeleton| skeleton.atlas_handle.path())
+            .and_then(|path| path.path().parent().map(Path::to_path_buf))
+        else {
+            warn!("no skeleton folder for spine event audio {}", audio_path);
+            continue;
+        };
+        commands.spawn(SpineEventSoundLoading {
+            source: asset_server.load(folder.join(audio_path)),
+            volume: *volume,
+            balance: *balance,
+            spine: *entity,
+        });
+    }
+}
+
+fn spine_event_audio_ready(
+    mut commands: Commands,
+    loading_query: Query<(Entity, &SpineEventSoundLoading)>,
+    sources: Res<Assets<AudioSource>>,
+    mut audios: ResMut<Assets<SpineEventAudio>>,
+    asset_server: Res<AssetServer>,
+    settings: Res<SpineAudioSettings>,
+) {
+    for (entity, loading) in loading_query.iter() {
+        if let Some(source) = sources.get(&loading.source) {
+            let handle = audios.add(SpineEventAudio {
+                source: source.clone(),
+                balance: loading.balance,
+            });
+            commands
+                .entity(entity)
+                .remove::<SpineEventSoundLoading>()
+                .insert((
+                    AudioPlayer(handle),
+                    PlaybackSettings::DESPAWN
+                        .with_volume(Volume::Linear(loading.volume * settings.volume)),
+                    SpineEventSound {
+                        spine: loading.spine,
+                    },
+                ));
+        } else if asset_server.load_state(&loading.source).is_failed() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
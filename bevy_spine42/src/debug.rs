@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+use rusty_spine::{Skeleton, Slot};
+
+/// Geometry of a [`Skeleton`] for debug drawing, in skeleton space.
+///
+/// Multiply by the [`GlobalTransform`] of the [`Spine`](`crate::Spine`) entity to get world
+/// positions.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_spine::prelude::*;
+/// fn log_bones(spine_query: Query<(&Spine, &GlobalTransform)>) {
+///     for (spine, transform) in spine_query.iter() {
+///         for bone in SkeletonDebug::new(&spine.skeleton).bones {
+///             let start = transform.transform_point(bone.start.extend(0.));
+///             info!("{} at {}", bone.name, start);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SkeletonDebug {
+    pub bones: Vec<DebugBone>,
+    /// Outlines of the visible region and mesh attachments, in draw order.
+    pub slots: Vec<DebugSlot>,
+    pub bounding_boxes: Vec<Vec<Vec2>>,
+    pub clippings: Vec<Vec<Vec2>>,
+    pub constraints: Vec<DebugConstraint>,
+}
+
+/// A bone from its origin to the end of its length.
+#[derive(Debug, Clone)]
+pub struct DebugBone {
+    pub name: String,
+    pub start: Vec2,
+    pub end: Vec2,
+}
+
+#[derive(Debug, Clone)]
+pub struct DebugSlot {
+    pub name: String,
+    pub polygon: Vec<Vec2>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugConstraintKind {
+    Ik,
+    Path,
+    Physics,
+}
+
+/// The target of a constraint and the origins of the bones it moves.
+#[derive(Debug, Clone)]
+pub struct DebugConstraint {
+    pub kind: DebugConstraintKind,
+    pub target: Vec2,
+    pub bones: Vec<Vec2>,
+}
+
+impl SkeletonDebug {
+    pub fn new(skeleton: &Skeleton) -> Self {
+        let mut debug = Self::default();
+        for bone in skeleton.bones() {
+            let start = Vec2::new(bone.world_x(), bone.world_y());
+            // the bone's x axis in world space
+            let end = start + Vec2::new(bone.a(), bone.c()) * bone.data().length();
+            debug.bones.push(DebugBone {
+                name: bone.data().name().to_string(),
+                start,
+                end,
+            });
+        }
+        for slot in skeleton.draw_order() {
+            let Some(attachment) = slot.attachment() else {
+                continue;
+            };
+            if let Some(region) = attachment.as_region() {
+                let mut vertices = [0.; 8];
+                unsafe {
+                    region.compute_world_vertices(&slot, &mut vertices, 0, 2);
+                }
+                debug.slots.push(DebugSlot {
+                    name: slot.data().name().to_string(),
+                    polygon: polygon(&vertices),
+                });
+            } else if let Some(mesh) = attachment.as_mesh() {
+                let vertices = vertex_attachment_vertices(&slot, mesh.world_vertices_length(), |s, v, n| unsafe {
+                    mesh.compute_world_vertices(s, 0, n, v, 0, 2);
+                });
+                // the hull vertices come first and outline the mesh
+                let hull = (mesh.hull_length() as usize).min(vertices.len());
+                debug.slots.push(DebugSlot {
+                    name: slot.data().name().to_string(),
+                    polygon: polygon(&vertices[..hull]),
+                });
+            } else if let Some(bounding_box) = attachment.as_bounding_box() {
+                let vertices =
+                    vertex_attachment_vertices(&slot, bounding_box.world_vertices_length(), |s, v, n| unsafe {
+                        bounding_box.compute_world_vertices(s, 0, n, v, 0, 2);
+                    });
+                debug.bounding_boxes.push(polygon(&vertices));
+            } else if let Some(clipping) = attachment.as_clipping() {
+                let vertices =
+                    vertex_attachment_vertices(&slot, clipping.world_vertices_length(), |s, v, n| unsafe {
+                        clipping.compute_world_vertices(s, 0, n, v, 0, 2);
+                    });
+                debug.clippings.push(polygon(&vertices));
+            }
+        }
+        for constraint in skeleton.ik_constraints() {
+            let target = constraint.target();
+            debug.constraints.push(DebugConstraint {
+                kind: DebugConstraintKind::Ik,
+                target: Vec2::new(target.world_x(), target.world_y()),
+                bones: constraint.bones().map(|b| Vec2::new(b.world_x(), b.world_y())).collect(),
+            });
+        }
+        for constraint in skeleton.path_constraints() {
+            let target = constraint.target();
+            let bone = target.bone();
+            debug.constraints.push(DebugConstraint {
+                kind: DebugConstraintKind::Path,
+                target: Vec2::new(bone.world_x(), bone.world_y()),
+                bones: constraint.bones().map(|b| Vec2::new(b.world_x(), b.world_y())).collect(),
+            });
+        }
+        for constraint in skeleton.physics_constraints() {
+            let bone = constraint.bone();
+            let position = Vec2::new(bone.world_x(), bone.world_y());
+            debug.constraints.push(DebugConstraint {
+                kind: DebugConstraintKind::Physics,
+                target: position,
+                bones: vec![position],
+            });
+        }
+        debug
+    }
+}
+
+fn vertex_attachment_vertices(slot: &Slot, length: i32, compute: impl Fn(&Slot, &mut [f32], i32)) -> Vec<f32> {
+    let mut vertices = vec![0.; length.max(0) as usize];
+    compute(slot, &mut vertices, length);
+    vertices
+}
+
+fn polygon(vertices: &[f32]) -> Vec<Vec2> {
+    vertices.chunks_exact(2).map(|v| Vec2::new(v[0], v[1])).collect()
+}
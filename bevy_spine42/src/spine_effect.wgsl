@@ -0,0 +1,109 @@
+#import bevy_sprite::mesh2d_view_bindings::view
+
+#ifdef TONEMAP_IN_SHADER
+#import bevy_core_pipeline::tonemapping
+#endif
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world_position: vec4<f32>,
+    @location(1) world_normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(4) color: vec4<f32>,
+    @location(10) dark_color: vec4<f32>,
+};
+
+struct SpineImageEffect {
+    grayscale: f32,
+    sepia: f32,
+    negative: f32,
+    blur: f32,
+    mosaic: f32,
+};
+
+struct SpineRuleParams {
+    rect: vec4<f32>,
+    progress: f32,
+    softness: f32,
+};
+
+@group(2) @binding(0)
+var texture: texture_2d<f32>;
+@group(2) @binding(1)
+var texture_sampler: sampler;
+@group(2) @binding(2)
+var<uniform> effect: SpineImageEffect;
+@group(2) @binding(3)
+var<uniform> rule_params: SpineRuleParams;
+@group(2) @binding(4)
+var rule_texture: texture_2d<f32>;
+@group(2) @binding(5)
+var rule_sampler: sampler;
+
+// samples with premultiplied alpha so that blurred edges do not bleed color
+fn sample(uv: vec2<f32>) -> vec4<f32> {
+    let c = textureSampleLevel(texture, texture_sampler, uv, 0.0);
+#ifdef PREMULTIPLIED_ALPHA
+    return c;
+#else
+    return vec4(c.rgb * c.a, c.a);
+#endif
+}
+
+@fragment
+fn fragment(
+    input: VertexOutput,
+) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(texture));
+    var uv = input.uv;
+    if effect.mosaic > 1.0 {
+        uv = (floor(uv * size / effect.mosaic) + 0.5) * effect.mosaic / size;
+    }
+    var tex = sample(uv);
+    if effect.blur > 0.0 {
+        let offset = effect.blur * 0.5 / size;
+        var sum = vec4(0.0);
+        for (var x = -2; x <= 2; x++) {
+            for (var y = -2; y <= 2; y++) {
+                sum += sample(uv + vec2(f32(x), f32(y)) * offset);
+            }
+        }
+        tex = sum / 25.0;
+    }
+
+    var rgb = tex.rgb / max(tex.a, 0.0001);
+    let gray = dot(rgb, vec3(0.299, 0.587, 0.114));
+    rgb = mix(rgb, vec3(gray), clamp(effect.grayscale, 0.0, 1.0));
+    let sepia = vec3(
+        dot(rgb, vec3(0.393, 0.769, 0.189)),
+        dot(rgb, vec3(0.349, 0.686, 0.168)),
+        dot(rgb, vec3(0.272, 0.534, 0.131)),
+    );
+    rgb = mix(rgb, min(sepia, vec3(1.0)), clamp(effect.sepia, 0.0, 1.0));
+    rgb = mix(rgb, vec3(1.0) - rgb, clamp(effect.negative, 0.0, 1.0));
+
+#ifdef PREMULTIPLIED_ALPHA
+    let tex_color = vec4(rgb * tex.a, tex.a);
+#else
+    let tex_color = vec4(rgb, tex.a);
+#endif
+    var color = vec4(
+        ((tex_color.a - 1.0) * input.dark_color.a + 1.0 - tex_color.rgb) * input.dark_color.rgb + tex_color.rgb * input.color.rgb,
+        tex_color.a * input.color.a,
+    );
+
+    // dark pixels of the rule image are revealed first, the image is upright in world space
+    let rule_uv = (input.world_position.xy - rule_params.rect.xy) / rule_params.rect.zw;
+    let value = textureSampleLevel(rule_texture, rule_sampler, vec2(rule_uv.x, 1.0 - rule_uv.y), 0.0).r;
+    let softness = max(rule_params.softness, 0.0001);
+    let coverage = clamp((rule_params.progress * (1.0 + softness) - value) / softness, 0.0, 1.0);
+#ifdef PREMULTIPLIED_ALPHA
+    color *= coverage;
+#else
+    color.a *= coverage;
+#endif
+#ifdef TONEMAP_IN_SHADER
+    color = tonemapping::tone_mapping(color, view.color_grading);
+#endif
+    return color;
+}
@@ -0,0 +1,365 @@
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+
+use crate::compare::{ActiveViewer, CompareButton, CompareMode};
+use crate::export::ExportRequest;
+use crate::game::{AnimeMenuList, FONT, HOVERBG, LISTTEXT, SELECTTEXT, ViewRes, Z_UI};
+use crate::spine_export::{EXPORT_FPS, EXPORT_SIZES, ExportQueue, ViewerBundle};
+use crate::spine_facade::{AnySpine, HasSpine, SkeletonMsg, with_spine};
+
+// seconds per single-step, the frame rate of most exports
+const STEP_TIME: f32 = 1. / 30.;
+pub(crate) const MAX_TRACK: usize = 8;
+
+/// Playback settings of the viewer spine, AnimeMenu buttons set animations on `track`
+#[derive(Resource)]
+pub(crate) struct AnimeControl {
+    pub(crate) track: usize,
+    pub(crate) timescale: f32,
+    pub(crate) paused: bool,
+    pub(crate) looping: bool,
+    // mix duration from the previous animation of the track
+    pub(crate) mix: f32,
+}
+
+impl Default for AnimeControl {
+    fn default() -> Self {
+        Self {
+            track: 0,
+            timescale: 1.,
+            paused: false,
+            looping: true,
+            mix: 0.2,
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+enum AnimeControlButton {
+    PlayPause,
+    StepBack,
+    StepForward,
+    Slower,
+    Faster,
+    Loop,
+    PrevTrack,
+    NextTrack,
+    LessMix,
+    MoreMix,
+    ClearTrack,
+    CycleFormat,
+    CycleSize,
+    CycleFps,
+    Export,
+}
+
+#[derive(Component)]
+struct AnimeTimeline;
+
+#[derive(Component)]
+struct AnimeTimelineFill;
+
+#[derive(Component)]
+struct AnimeStatus;
+
+pub(crate) struct AnimeControlPlugin;
+
+impl Plugin for AnimeControlPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AnimeControl>()
+            .add_systems(Update, (anime_control, anime_timeline, anime_status).chain());
+    }
+}
+
+// tagged AnimeMenuList so it goes away with the animation list
+pub(crate) fn spawn_anime_control(commands: &mut Commands, asset_server: &AssetServer) {
+    let font = TextFont {
+        font: asset_server.load(FONT).into(),
+        font_size: FontSize::Px(30.),
+        ..default()
+    };
+    commands.spawn((
+        Visibility::Visible,
+        AnimeMenuList,
+        ZIndex(Z_UI),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(50.),
+            left: Val::Percent(13.),
+            bottom: Val::Percent(1.),
+            flex_direction: FlexDirection::Column,
+            row_gap: px(8),
+            ..default()
+        },
+    )).with_children(|parent| {
+        parent.spawn(Node {
+            flex_wrap: FlexWrap::Wrap,
+            column_gap: px(18),
+            ..default()
+        }).with_children(|parent| {
+            for (label, button) in [
+                ("Play/Pause", AnimeControlButton::PlayPause),
+                ("<|", AnimeControlButton::StepBack),
+                ("|>", AnimeControlButton::StepForward),
+                ("Speed-", AnimeControlButton::Slower),
+                ("Speed+", AnimeControlButton::Faster),
+                ("Loop", AnimeControlButton::Loop),
+                ("Track-", AnimeControlButton::PrevTrack),
+                ("Track+", AnimeControlButton::NextTrack),
+                ("Mix-", AnimeControlButton::LessMix),
+                ("Mix+", AnimeControlButton::MoreMix),
+                ("Clear", AnimeControlButton::ClearTrack),
+                ("Format", AnimeControlButton::CycleFormat),
+                ("Size", AnimeControlButton::CycleSize),
+                ("FPS", AnimeControlButton::CycleFps),
+                ("Export", AnimeControlButton::Export),
+            ] {
+                parent.spawn((
+                    Button,
+                    Text::new(label),
+                    button,
+                    font.clone(),
+                    TextColor(LISTTEXT),
+                    BackgroundColor(Color::NONE),
+                ));
+            }
+            for (label, button) in [
+                ("Compare", CompareButton::Compare),
+                ("Sync", CompareButton::Sync),
+                ("Next", CompareButton::Next),
+            ] {
+                parent.spawn((
+                    Button,
+                    Text::new(label),
+                    button,
+                    font.clone(),
+                    TextColor(LISTTEXT),
+                    BackgroundColor(Color::NONE),
+                ));
+            }
+        });
+        parent.spawn((
+            Button,
+            AnimeTimeline,
+            RelativeCursorPosition::default(),
+            Node {
+                width: Val::Percent(100.),
+                height: px(18),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0., 0., 0., 0.4)),
+        )).with_child((
+            AnimeTimelineFill,
+            Node {
+                width: Val::Percent(0.),
+                height: Val::Percent(100.),
+                ..default()
+            },
+            BackgroundColor(HOVERBG),
+        ));
+        parent.spawn((
+            AnimeStatus,
+            Text::new(""),
+            font,
+            TextColor(SELECTTEXT),
+        ));
+    });
+}
+
+fn anime_control(
+    mut interaction_query: Query<(
+        &Interaction,
+        &AnimeControlButton,
+        &mut TextColor,
+        &mut BackgroundColor,
+    ), (Changed<Interaction>, With<Button>)>,
+    mut spine_query: Query<(AnySpine, &ViewerBundle, Has<ActiveViewer>), HasSpine>,
+    mut control: ResMut<AnimeControl>,
+    mut export: ResMut<ExportQueue>,
+    view_res: Res<ViewRes>,
+) {
+    for (interaction, button, mut color, mut bg_color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {}
+            Interaction::Hovered => {
+                *color = SELECTTEXT.into();
+                *bg_color = HOVERBG.into();
+                continue;
+            }
+            _ => {
+                *color = LISTTEXT.into();
+                *bg_color = Color::NONE.into();
+                continue;
+            }
+        }
+        match button {
+            AnimeControlButton::PlayPause => control.paused = !control.paused,
+            AnimeControlButton::StepBack | AnimeControlButton::StepForward => control.paused = true,
+            AnimeControlButton::Slower => control.timescale = (control.timescale - 0.1).max(0.1),
+            AnimeControlButton::Faster => control.timescale = (control.timescale + 0.1).min(4.),
+            AnimeControlButton::Loop => control.looping = !control.looping,
+            AnimeControlButton::PrevTrack => control.track = control.track.saturating_sub(1),
+            AnimeControlButton::NextTrack => control.track = (control.track + 1).min(MAX_TRACK),
+            AnimeControlButton::LessMix => control.mix = (control.mix - 0.1).max(0.),
+            AnimeControlButton::MoreMix => control.mix = (control.mix + 0.1).min(2.),
+            AnimeControlButton::CycleFormat => export.settings.format = export.settings.format.next(),
+            AnimeControlButton::CycleSize => {
+                let size = (export.settings.width, export.settings.height);
+                let i = EXPORT_SIZES.iter().position(|s| *s == size).map_or(0, |i| (i + 1) % EXPORT_SIZES.len());
+                (export.settings.width, export.settings.height) = EXPORT_SIZES[i];
+            }
+            AnimeControlButton::CycleFps => {
+                let i = EXPORT_FPS.iter().position(|f| *f == export.settings.fps).map_or(0, |i| (i + 1) % EXPORT_FPS.len());
+                export.settings.fps = EXPORT_FPS[i];
+            }
+            AnimeControlButton::ClearTrack | AnimeControlButton::Export => {}
+        }
+        // compared skeletons only share the timescale
+        for (spine, bundle, _) in spine_query.iter_mut().filter(|(_, _, active)| *active) {
+            let track = control.track;
+            with_spine!(spine, |spine| match button {
+                AnimeControlButton::StepBack | AnimeControlButton::StepForward => {
+                    let step = if matches!(button, AnimeControlButton::StepBack) { -STEP_TIME } else { STEP_TIME };
+                    if let Some(mut entry) = spine.animation_state.track_at_index_mut(track) {
+                        let time = (entry.track_time() + step).max(0.);
+                        entry.set_track_time(time);
+                    }
+                }
+                AnimeControlButton::Loop => {
+                    if let Some(mut entry) = spine.animation_state.track_at_index_mut(track) {
+                        entry.set_looping(control.looping);
+                    }
+                }
+                AnimeControlButton::ClearTrack => {
+                    if track == 0 {
+                        spine.animation_state.clear_track(0);
+                        spine.skeleton.set_to_setup_pose();
+                    } else {
+                        // fade the layer out instead of snapping to the lower tracks
+                        spine.animation_state.set_empty_animation(track, control.mix);
+                    }
+                }
+                AnimeControlButton::Export => {
+                    // the animation on track 0, upper tracks are not exported
+                    let animation = spine.animation_state.get_current(0).map(|e| e.animation().name().to_string());
+                    if let Some(file) = view_res.spines.get(&bundle.0) {
+                        let request = ExportRequest {
+                            bundle: bundle.0.clone(),
+                            animation,
+                            settings: export.settings.clone(),
+                        };
+                        export.pending.push_back((request, file.clone()));
+                    }
+                }
+                _ => {}
+            });
+        }
+    }
+    if control.is_changed() {
+        for (spine, ..) in spine_query.iter_mut() {
+            let timescale = if control.paused { 0. } else { control.timescale };
+            with_spine!(spine, |spine| spine.animation_state.set_timescale(timescale));
+        }
+    }
+}
+
+// click or drag on the timeline to scrub the current track
+fn anime_timeline(
+    timeline: Single<(&Interaction, &RelativeCursorPosition), With<AnimeTimeline>>,
+    mut fill: Single<&mut Node, With<AnimeTimelineFill>>,
+    mut spine_query: Query<AnySpine, (HasSpine, With<ActiveViewer>)>,
+    mut control: ResMut<AnimeControl>,
+) {
+    let (interaction, cursor) = *timeline;
+    let track = control.track;
+    let Some(spine) = spine_query.iter_mut().next() else { return };
+    with_spine!(spine, |spine| {
+        let Some(mut entry) = spine.animation_state.track_at_index_mut(track) else {
+            fill.width = Val::Percent(0.);
+            return;
+        };
+        let start = entry.animation_start();
+        let length = entry.animation_end() - start;
+        if length <= 0. {
+            return;
+        }
+        if *interaction == Interaction::Pressed && let Some(position) = cursor.normalized {
+            // normalized is relative to the node center
+            let ratio = (position.x + 0.5).clamp(0., 1.);
+            // the timeline spans animation_start..animation_end, the track time counts from
+            // animation_start and keeps its loop count so Complete is not fired again
+            let time = start + ratio * length;
+            let loops = if entry.looping() { (entry.track_time() / length).floor() } else { 0. };
+            entry.set_track_time(loops * length + time - start);
+            if !control.paused {
+                control.paused = true;
+            }
+        }
+        let ratio = ((entry.animation_time() - start) / length).clamp(0., 1.);
+        fill.width = Val::Percent(ratio * 100.);
+    });
+}
+
+fn anime_status(
+    mut status: Single<&mut Text, With<AnimeStatus>>,
+    spine_query: Query<(Entity, AnySpine), (HasSpine, With<ActiveViewer>)>,
+    mut skeleton_msg: MessageReader<SkeletonMsg>,
+    mut last_msg: Local<Option<(Entity, String)>>,
+    control: Res<AnimeControl>,
+    compare: Res<CompareMode>,
+    export: Res<ExportQueue>,
+) {
+    let Some((entity, spine)) = spine_query.iter().next() else { return };
+    // the latest animation state or event message of the active skeleton
+    for msg in skeleton_msg.read().filter(|msg| msg.entity() == entity) {
+        let text = match msg {
+            SkeletonMsg::Start { animation, .. } => format!("Start {}", animation),
+            SkeletonMsg::Interrupt { animation, .. } => format!("Interrupt {}", animation),
+            SkeletonMsg::End { animation, .. } => format!("End {}", animation),
+            SkeletonMsg::Complete { animation, .. } => format!("Complete {}", animation),
+            SkeletonMsg::Dispose { .. } => continue,
+            SkeletonMsg::Event { name, int, float, string, .. } => format!("Event {} {} {:.2} {:?}", name, int, float, string),
+        };
+        *last_msg = Some((entity, text));
+    }
+    let animation = with_spine!(spine, |spine| match spine.animation_state.get_current(control.track) {
+        Some(entry) => format!(
+            "{} {:.2}/{:.2}s",
+            entry.animation().name(),
+            entry.animation_time() - entry.animation_start(),
+            entry.animation_end() - entry.animation_start(),
+        ),
+        None => "-".to_string(),
+    }).unwrap_or_default();
+    let last = match last_msg.as_ref() {
+        Some((last, text)) if *last == entity => format!("  {}", text),
+        _ => String::new(),
+    };
+    let exporting = match &export.job {
+        Some(job) => format!(" ({}/{})", job.frame, job.frame_count),
+        None => String::new(),
+    };
+    let comparing = match compare.enabled {
+        true => format!("  Compare {}{}", compare.order.len(), if compare.sync { " (sync)" } else { "" }),
+        false => String::new(),
+    };
+    let text = format!(
+        "Track {}: {}{}  x{:.1}{}  {}  Mix {:.1}s  Export {:?} {}x{} {}fps{}{}",
+        control.track,
+        animation,
+        last,
+        control.timescale,
+        if control.paused { " (paused)" } else { "" },
+        if control.looping { "Loop" } else { "Once" },
+        control.mix,
+        export.settings.format,
+        export.settings.width,
+        export.settings.height,
+        export.settings.fps,
+        exporting,
+        comparing,
+    );
+    if status.0 != text {
+        status.0 = text;
+    }
+}
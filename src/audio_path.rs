@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Finds the audio file for a sheet FileName, trying each extension in order.
+/// Results, including missing files, are cached so the disk is checked once per name.
+#[derive(Resource)]
+pub struct AudioPaths {
+    root: String,
+    extensions: Vec<String>,
+    cache: HashMap<String, Option<String>>,
+}
+
+impl AudioPaths {
+    pub fn new(root: &str, extensions: &[String]) -> Self {
+        Self {
+            root: root.into(),
+            extensions: extensions.to_vec(),
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Returns the asset path of `dir` + `file_name`, the extension of `file_name` is ignored
+    pub fn resolve(&mut self, dir: &str, file_name: &str) -> Option<String> {
+        let stem = match file_name.rfind('.') {
+            Some(i) if !file_name[i..].contains('/') => &file_name[..i],
+            _ => file_name,
+        };
+        if stem.is_empty() {
+            warn!("empty audio file name in {}", dir);
+            return None;
+        }
+        let key = format!("{}{}", dir, stem);
+        if let Some(path) = self.cache.get(&key) {
+            return path.clone();
+        }
+        let lower = stem.to_lowercase();
+        let mut stems = vec![lower.as_str()];
+        if lower != stem {
+            stems.push(stem);
+        }
+        let path = stems.iter()
+            .flat_map(|s| self.extensions.iter().map(move |ext| format!("{}{}.{}", dir, s, ext)))
+            .find(|p| Path::new(&self.root).join(p).exists());
+        if path.is_none() {
+            warn!("audio file not found: {}{} ({})", dir, stem, self.extensions.join("/"));
+        }
+        self.cache.insert(key, path.clone());
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{File, create_dir_all, remove_dir_all};
+
+    fn assets(name: &str, files: &[&str]) -> String {
+        let root = std::env::temp_dir().join(format!("moe_priest_audio_{}", name));
+        let _ = remove_dir_all(&root);
+        for file in files {
+            let path = root.join(file);
+            create_dir_all(path.parent().unwrap()).unwrap();
+            File::create(path).unwrap();
+        }
+        root.to_string_lossy().into_owned()
+    }
+
+    fn extensions() -> Vec<String> {
+        vec!["ogg".into(), "wav".into()]
+    }
+
+    #[test]
+    fn extension_order() {
+        let root = assets("order", &["bgm/title.wav", "bgm/title.ogg", "bgm/battle.wav"]);
+        let mut paths = AudioPaths::new(&root, &extensions());
+        assert_eq!(paths.resolve("bgm/", "title").as_deref(), Some("bgm/title.ogg"));
+        assert_eq!(paths.resolve("bgm/", "battle").as_deref(), Some("bgm/battle.wav"));
+    }
+
+    #[test]
+    fn sheet_extension_ignored() {
+        let root = assets("sheet", &["se/click.ogg", "se/v1.2/click.ogg"]);
+        let mut paths = AudioPaths::new(&root, &extensions());
+        assert_eq!(paths.resolve("se/", "click.mp3").as_deref(), Some("se/click.ogg"));
+        // a dot in a folder of the file name is not an extension
+        assert_eq!(paths.resolve("se/", "v1.2/click").as_deref(), Some("se/v1.2/click.ogg"));
+    }
+
+    #[test]
+    fn lowercase_first() {
+        let root = assets("case", &["voice/ch01_001.ogg", "voice/CH01_001.ogg", "voice/CH01_002.ogg"]);
+        let mut paths = AudioPaths::new(&root, &extensions());
+        assert_eq!(paths.resolve("voice/", "CH01_001").as_deref(), Some("voice/ch01_001.ogg"));
+        assert_eq!(paths.resolve("voice/", "CH01_002").as_deref(), Some("voice/CH01_002.ogg"));
+    }
+
+    #[test]
+    fn missing_is_cached() {
+        let root = assets("missing", &[]);
+        let mut paths = AudioPaths::new(&root, &extensions());
+        assert_eq!(paths.resolve("se/", "door"), None);
+        assets("missing", &["se/door.ogg"]);
+        assert_eq!(paths.resolve("se/", "door"), None);
+        assert_eq!(AudioPaths::new(&root, &extensions()).resolve("se/", "door").as_deref(), Some("se/door.ogg"));
+    }
+
+    #[test]
+    fn empty_name() {
+        let root = assets("empty", &["se/.ogg"]);
+        let mut paths = AudioPaths::new(&root, &extensions());
+        assert_eq!(paths.resolve("se/", ""), None);
+        assert_eq!(paths.resolve("se/", ".ogg"), None);
+    }
+}
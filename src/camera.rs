@@ -0,0 +1,171 @@
+use bevy::camera::visibility::RenderLayers;
+use bevy::prelude::*;
+use bevy::ui::IsDefaultUiCamera;
+use bevy_auto_scaling::{AspectRatio, fixed_size_2d};
+use bevy_tweening::{AnimTarget, Lens, TweenAnim, lens::*};
+use std::time::Duration;
+
+use crate::game::{LIST_LEFT, VNGui, VNSpine, VNTexture, ViewRes, WaitEffect, Z_CG, f32, should_wait};
+use crate::input_map::{Action, ActionState};
+use crate::scene_filter::SceneFilter;
+use crate::spine_tween::ShakeAnim;
+use crate::utage4;
+
+// render layer of the message window, drawn by the gui camera which never moves
+pub(crate) const GUI_LAYER: usize = 1;
+pub(crate) const VIEW_SIZE: Vec2 = Vec2::new(1920. * 1.14514, 1080. * 1.14514);
+
+/// The scene camera, target of Camera tweens and Zoom
+#[derive(Component)]
+pub(crate) struct VNCamera {
+    zoom: f32,
+}
+
+struct ProjectionScaleLens {
+    start: f32,
+    end: f32,
+}
+
+impl Lens<Projection> for ProjectionScaleLens {
+    fn lerp(&mut self, mut target: Mut<Projection>, ratio: f32) {
+        if let Projection::Orthographic(ortho) = target.as_mut() {
+            ortho.scale = self.start + (self.end - self.start) * ratio;
+        }
+    }
+}
+
+fn scene_projection(scale: f32) -> Projection {
+    let mut projection = fixed_size_2d(VIEW_SIZE.x, VIEW_SIZE.y);
+    if let Projection::Orthographic(ortho) = &mut projection {
+        ortho.scale = scale;
+    }
+    projection
+}
+
+pub(crate) fn spawn_cameras(commands: &mut Commands) {
+    commands.spawn((
+        Camera2d,
+        VNCamera {
+            zoom: 1.,
+        },
+        AspectRatio(16. / 9.),
+        scene_projection(1.),
+    ));
+    // VNGui and ui nodes are drawn on top of the scene
+    commands.spawn((
+        Camera2d,
+        Camera {
+            order: 1,
+            clear_color: ClearColorConfig::None,
+            ..default()
+        },
+        IsDefaultUiCamera,
+        RenderLayers::layer(GUI_LAYER),
+        AspectRatio(16. / 9.),
+        fixed_size_2d(VIEW_SIZE.x, VIEW_SIZE.y),
+    ));
+}
+
+pub(crate) fn reset_camera(
+    commands: &mut Commands,
+    camera: Entity,
+    vn_camera: &mut VNCamera,
+    transform: &mut Transform,
+) {
+    vn_camera.zoom = 1.;
+    *transform = Transform::default();
+    commands.entity(camera)
+        .remove::<ShakeAnim>()
+        .insert(scene_projection(1.));
+}
+
+pub(crate) fn zoom_cmd(
+    node: &utage4::Node,
+    commands: &mut Commands,
+    gui_query: &mut Query<(Entity, Option<&mut VNCamera>, &mut Transform), (Or<(With<VNGui>, With<VNCamera>)>, Without<VNSpine>, Without<VNTexture>)>,
+) {
+    // Arg2 zoom, Arg3 Arg4 zoom center in screen ratio (0.5 is the center), Arg6 time
+    f32!(zoom = node.arg2, 1.);
+    f32!(center_x = node.arg3, 0.5);
+    f32!(center_y = node.arg4, 0.5);
+    f32!(time = node.arg6, 0.);
+    if zoom <= 0. {
+        warn!("Invalid zoom: {:?}", node.arg2);
+        return
+    }
+    let Some((camera, Some(mut vn_camera), mut transform)) = gui_query.iter_mut().find(|x| x.1.is_some()) else {
+        return
+    };
+    let start_scale = 1. / vn_camera.zoom;
+    let end_scale = 1. / zoom;
+    // keep the zoom center at the same place on screen
+    let start = transform.translation;
+    let center = start + ((vec2(center_x, center_y) - 0.5) * VIEW_SIZE * start_scale).extend(0.);
+    let end = center - (center - start) * end_scale / start_scale;
+    info!("Zoom {} at ({}, {})", zoom, center_x, center_y);
+    vn_camera.zoom = zoom;
+    if time > 0. {
+        let duration = Duration::from_secs_f32(time);
+        let mut cmd = commands.spawn((
+            TweenAnim::new(bevy_tweening::Tween::new(
+                EaseFunction::Linear,
+                duration,
+                ProjectionScaleLens { start: start_scale, end: end_scale },
+            )),
+            AnimTarget::component::<Projection>(camera),
+        ));
+        if should_wait(&node.wait_type) {
+            cmd.insert(WaitEffect);
+        }
+        let mut cmd = commands.spawn((
+            TweenAnim::new(bevy_tweening::Tween::new(
+                EaseFunction::Linear,
+                duration,
+                TransformPositionLens { start, end },
+            )),
+            AnimTarget::component::<Transform>(camera),
+        ));
+        if should_wait(&node.wait_type) {
+            cmd.insert(WaitEffect);
+        }
+    } else {
+        transform.translation = end;
+        commands.entity(camera).insert(scene_projection(end_scale));
+    }
+}
+
+/// Framing of the viewer skeleton computed when it loads, restored by reset_view
+#[derive(Component)]
+pub(crate) struct ViewerFit(pub(crate) Transform);
+
+/// Setup pose bounds of a viewer skeleton
+#[derive(Component)]
+pub(crate) struct SkeletonBounds(pub(crate) Rect);
+
+// fit the bounds into one of `columns` side by side parts of the view left of the scene list
+pub(crate) fn fit_transform(bounds: Rect, column: usize, columns: usize) -> Transform {
+    let column_width = VIEW_SIZE.x * LIST_LEFT / columns.max(1) as f32;
+    let area_center = vec2(-VIEW_SIZE.x / 2. + column_width * (column as f32 + 0.5), 0.);
+    if bounds.width() <= 0. || bounds.height() <= 0. {
+        return Transform::from_translation(area_center.extend(Z_CG as f32)).with_scale(Vec3::ONE * 0.5);
+    }
+    let area = vec2(column_width, VIEW_SIZE.y) * 0.9;
+    let scale = (area.x / bounds.width()).min(area.y / bounds.height());
+    Transform::from_translation((area_center - bounds.center() * scale).extend(Z_CG as f32)).with_scale(Vec3::splat(scale))
+}
+
+pub(crate) fn reset_view(
+    actions: Res<ActionState>,
+    mut spine_query: Query<(&ViewerFit, &mut Transform)>,
+    filter: Res<SceneFilter>,
+    view_res: Res<ViewRes>,
+) {
+    if view_res.avg || filter.focused {
+        return;
+    }
+    if actions.just_pressed(Action::ResetView) {
+        for (fit, mut transform) in spine_query.iter_mut() {
+            *transform = fit.0;
+        }
+    }
+}
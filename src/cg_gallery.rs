@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::read_to_string;
+
+use crate::camera::ViewerFit;
+use crate::cli::asset_path;
+use crate::game::{FONT, HEADTEXT, THUMBNAIL, ViewRes, Z_CG, spawn_scene_cell, texture_folder};
+use crate::input_map::{Action, ActionState};
+use crate::scene_filter::SceneFilter;
+use crate::spine_facade::HasSpine;
+use crate::thumbnail::OffscreenSpine;
+use crate::utage4::{TextureEntry, VNConfig};
+
+const CG_CONFIG: &str = "config.cg.json";
+// category of event textures without a CgCategolly
+const CG_DEFAULT_CATEGORY: &str = "Event";
+
+/// Labels of gallery textures shown at least once, saved to CG_CONFIG on change
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct CgGallery {
+    pub(crate) unlocked: BTreeSet<String>,
+}
+
+impl CgGallery {
+    pub(crate) fn load() -> Self {
+        read_to_string(CG_CONFIG).ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn save(&self) {
+        match serde_json::to_string_pretty(self) {
+            Ok(content) => if let Err(e) = std::fs::write(CG_CONFIG, content) {
+                warn!("failed to save {}: {}", CG_CONFIG, e);
+            },
+            Err(e) => warn!("failed to serialize cg gallery: {}", e),
+        }
+    }
+}
+
+pub(crate) fn unlock_cg(cg: &mut ResMut<CgGallery>, label: &str) {
+    // only touch the resource for new labels so it is saved once
+    if !cg.unlocked.contains(label) {
+        cg.unlocked.insert(label.to_string());
+    }
+}
+
+/// Full screen image of a gallery entry, zoomed and panned like the viewer spine
+#[derive(Component)]
+pub(crate) struct CgView;
+
+pub(crate) struct CgGalleryPlugin;
+
+impl Plugin for CgGalleryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CgGallery::load())
+            .add_systems(Update, (cg_view, save_cg_gallery));
+    }
+}
+
+/// Gallery category of a texture, Utage lists textures with a CgCategolly in its gallery
+pub(crate) fn cg_category(texture: &TextureEntry) -> Option<&str> {
+    match (texture.cg_categolly.as_deref(), texture.entry_type.as_deref()) {
+        (Some(category), _) if !category.is_empty() => Some(category),
+        (_, Some("Event")) => Some(CG_DEFAULT_CATEGORY),
+        _ => None,
+    }
+}
+
+// texture labels by category
+fn cg_categories(vn: &VNConfig) -> BTreeMap<String, Vec<String>> {
+    let mut categories: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (label, texture) in vn.texture.iter() {
+        let Some(category) = cg_category(texture) else { continue };
+        categories.entry(category.to_string()).or_default().push(label.clone());
+    }
+    categories.values_mut().for_each(|labels| labels.sort());
+    categories
+}
+
+// image path and scale from the texture type, event images when the sheet has none
+fn cg_image(label: &str, view_res: &ViewRes) -> Option<(String, f32)> {
+    let texture = view_res.vn.texture.get(label)?;
+    let (folder, scale) = texture_folder(texture, "BgEvent")?;
+    Some((format!("{}{}", folder, texture.file_name.as_deref()?), scale))
+}
+
+// the Thumbnail column when the file exists, else the image itself
+pub(crate) fn cg_thumbnail(label: &str, view_res: &ViewRes) -> Option<String> {
+    let texture = view_res.vn.texture.get(label)?;
+    if let Some(thumbnail) = texture.thumbnail.as_deref().filter(|t| !t.is_empty()) {
+        let path = format!("{}{}", *THUMBNAIL, thumbnail);
+        if asset_path(&path).exists() {
+            return Some(path);
+        }
+    }
+    cg_image(label, view_res).map(|(path, _)| path)
+}
+
+pub(crate) fn spawn_cg_cells(
+    parent: &mut ChildSpawnerCommands,
+    asset_server: &AssetServer,
+    view_res: &ViewRes,
+    cg: &CgGallery,
+) {
+    for (category, labels) in cg_categories(&view_res.vn) {
+        parent.spawn((
+            Text::new(category),
+            TextFont {
+                font: asset_server.load(FONT).into(),
+                font_size: FontSize::Px(35.),
+                ..default()
+            },
+            TextColor(HEADTEXT),
+            Node {
+                grid_column: GridPlacement::span(3),
+                ..default()
+            },
+        ));
+        for label in labels {
+            let name = if cg.unlocked.contains(&label) { label.as_str() } else { "???" };
+            spawn_scene_cell(parent, asset_server, &label, name);
+        }
+    }
+}
+
+pub(crate) fn open_cg(
+    label: &str,
+    asset_server: &AssetServer,
+    commands: &mut Commands,
+    spine_query: &Query<Entity, (HasSpine, Without<OffscreenSpine>)>,
+    view_res: &ViewRes,
+    cg: &CgGallery,
+) {
+    if !cg.unlocked.contains(label) {
+        return;
+    }
+    let Some((path, scale)) = cg_image(label, view_res) else { return };
+    spine_query.iter().for_each(|entity| {
+        commands.entity(entity).despawn()
+    });
+    let fit = Transform::from_xyz(0., 0., Z_CG as f32).with_scale(Vec3::ONE * scale);
+    commands.spawn((
+        CgView,
+        Sprite {
+            image: asset_server.load(path),
+            ..default()
+        },
+        fit,
+        ViewerFit(fit),
+    ));
+}
+
+// ui nodes are drawn over sprites, hide them while a cg is open
+fn cg_view(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    added: Query<(), Added<CgView>>,
+    view_query: Query<Entity, With<CgView>>,
+    mut ui_query: Query<(Entity, &mut Visibility), (With<Node>, Without<ChildOf>)>,
+    mut hidden: Local<Vec<Entity>>,
+    filter: Res<SceneFilter>,
+) {
+    if !added.is_empty() && hidden.is_empty() {
+        for (entity, mut visibility) in ui_query.iter_mut() {
+            // the vn gui is already hidden outside of memory books
+            if *visibility != Visibility::Hidden {
+                *visibility = Visibility::Hidden;
+                hidden.push(entity);
+            }
+        }
+    }
+    if view_query.is_empty() || filter.focused || !actions.just_pressed(Action::Exit) {
+        return;
+    }
+    view_query.iter().for_each(|entity| {
+        commands.entity(entity).despawn()
+    });
+    for entity in hidden.drain(..) {
+        if let Ok((_, mut visibility)) = ui_query.get_mut(entity) {
+            *visibility = Visibility::Visible;
+        }
+    }
+}
+
+fn save_cg_gallery(cg: Res<CgGallery>) {
+    if cg.is_changed() && !cg.is_added() {
+        cg.save();
+    }
+}
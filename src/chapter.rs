@@ -0,0 +1,312 @@
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs::read_to_string;
+
+use crate::cli::asset_path;
+use crate::game::{FONT, HEADTEXT, HOVERBG, LISTTEXT, SELECTTEXT, VNToogleMsg, ViewRes, Z_UI};
+use crate::input_map::{Action, ActionState};
+use crate::scene_filter::SceneFilter;
+use crate::utage4;
+
+// characters of the first line shown under a chapter label
+const PREVIEW_LENGTH: usize = 40;
+
+/// A `*label` of a memory book and the first line of text after it
+#[derive(Clone, Debug)]
+pub(crate) struct Chapter {
+    label: String,
+    index: usize,
+    preview: String,
+}
+
+#[derive(Message)]
+pub(crate) struct ChapterMsg {
+    pub(crate) bundle: String,
+    pub(crate) chapters: Vec<Chapter>,
+}
+
+#[derive(Component)]
+pub(crate) struct ChapterList;
+
+/// Starts the book at node `index`, 0 plays it from the top
+#[derive(Component)]
+struct ChapterButton {
+    bundle: String,
+    index: usize,
+}
+
+pub(crate) struct ChapterPlugin;
+
+impl Plugin for ChapterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<ChapterMsg>()
+            .add_systems(Update, (list_chapters, choose_chapter, chapter_list_scroll).chain());
+    }
+}
+
+pub(crate) fn load_book(bundle_name: &str, view_res: &ViewRes) -> Option<Vec<utage4::Node>> {
+    let file = view_res.events.get(bundle_name)?;
+    let content = read_to_string(asset_path(format!("{}/{}.{}", file.path, file.name, file.ext))).ok()?;
+    Some(utage4::parse_book(content))
+}
+
+fn is_label(node: &utage4::Node) -> bool {
+    node.command.as_deref().is_some_and(|c| c.starts_with('*'))
+}
+
+pub(crate) fn book_chapters(book: &[utage4::Node], tag_regex: &Regex) -> Vec<Chapter> {
+    book.iter().enumerate()
+        .filter(|(_, node)| is_label(node))
+        .map(|(index, node)| {
+            let preview = book[index + 1..].iter()
+                .take_while(|n| !is_label(n))
+                .find_map(|n| n.text.as_deref())
+                .map(|text| tag_regex.replace_all(text, "").replace('\n', " ").chars().take(PREVIEW_LENGTH).collect())
+                .unwrap_or_default();
+            Chapter {
+                label: node.command.clone().unwrap_or_default(),
+                index,
+                preview,
+            }
+        })
+        .collect()
+}
+
+/// Commands that put the stage in the state left by `nodes`: images, characters, looping sounds and params.
+/// Jumps and selections are not followed, the book is read top to bottom.
+fn rebuild_state(nodes: &[utage4::Node]) -> Vec<utage4::Node> {
+    let layer = |i: usize| nodes[i].arg3.as_deref();
+    let mut flags = vec!();
+    let (mut bg, mut event, mut bgm, mut ambience, mut window) = (None, None, None, None, None);
+    let mut sprites: Vec<usize> = vec!();
+    let mut characters: Vec<usize> = vec!();
+    for (i, node) in nodes.iter().enumerate() {
+        match node.command.as_deref() {
+            None => if node.arg2.is_some() && let Some(name) = node.arg1.as_deref() {
+                characters.retain(|&c| nodes[c].arg1.as_deref() != Some(name));
+                if node.arg2.as_deref() != Some("<Off>") {
+                    characters.push(i);
+                }
+            },
+            Some("CharacterOff") => match node.arg1.as_deref() {
+                None => characters.clear(),
+                Some(l) => characters.retain(|&c| nodes[c].arg1.as_deref() != Some(l) && layer(c) != Some(l)),
+            },
+            Some("Bg") => bg = Some(i),
+            Some("BgEvent") => event = Some(i),
+            Some("BgOff") => bg = None,
+            Some("BgEventOff") => event = None,
+            Some("Sprite") => {
+                sprites.retain(|&s| nodes[s].arg1 != node.arg1);
+                sprites.push(i);
+            }
+            Some("SpriteOff") => match node.arg1.as_deref() {
+                None | Some("AllSpriteObjects") => sprites.clear(),
+                Some(l) => sprites.retain(|&s| nodes[s].arg1.as_deref() != Some(l) && layer(s) != Some(l)),
+            },
+            Some("LayerOff") => {
+                let off = |i: usize| node.arg1.is_none() || layer(i) == node.arg1.as_deref();
+                characters.retain(|&c| !off(c));
+                sprites.retain(|&s| !off(s));
+                bg = bg.filter(|&b| !off(b));
+                event = event.filter(|&e| !off(e));
+            }
+            Some("Bgm") => bgm = Some(i),
+            Some("Ambience") => ambience = Some(i),
+            Some("StopBgm") => bgm = None,
+            Some("StopAmbience") => ambience = None,
+            Some("StopSound") => {
+                let parts = node.arg1.as_deref().unwrap_or("Bgm,Ambience");
+                if parts == "All" || parts.contains("Bgm") {
+                    bgm = None;
+                }
+                if parts == "All" || parts.contains("Ambience") {
+                    ambience = None;
+                }
+            }
+            Some("HideMessageWindow") | Some("ShowMessageWindow") => window = Some(i),
+            Some("Param") | Some("SkipOff") | Some("SkipOn") | Some("AutoOn") | Some("AutoOff") => flags.push(i),
+            _ => {}
+        }
+    }
+    flags.into_iter()
+        .chain(bg)
+        .chain(event)
+        .chain(sprites)
+        .chain(characters)
+        .chain(bgm)
+        .chain(ambience)
+        .chain(window)
+        .map(|i| utage4::Node {
+            // characters come back without their line and nothing waits
+            text: None,
+            translations: HashMap::new(),
+            voice: None,
+            page_ctrl: None,
+            wait_type: None,
+            ..nodes[i].clone()
+        })
+        .collect()
+}
+
+// the state before index is rebuilt in front of it, so labels still jump to the original nodes
+pub(crate) fn start_book(
+    mut book: Vec<utage4::Node>,
+    index: usize,
+    vn_ui_msg: &mut MessageWriter<VNToogleMsg>,
+    view_res: &mut ResMut<ViewRes>,
+) {
+    let index = index.min(book.len());
+    if index > 0 {
+        let state = rebuild_state(&book[..index]);
+        book.splice(index..index, state);
+    }
+    view_res.avg = true;
+    view_res.avg_nodes = book;
+    view_res.avg_offset = index;
+    view_res.fast = false;
+    view_res.skip_forbidden = false;
+    view_res.window_hidden = false;
+    view_res.wait_timer = None;
+    view_res.effect_wait = false;
+    view_res.params = HashMap::new();
+    view_res.avg_labels = view_res.avg_nodes.iter().enumerate()
+        .filter(|(_, n)| is_label(n))
+        .map(|(i, n)| (n.command.clone().unwrap(), i))
+        .collect();
+    view_res.selection = None;
+    vn_ui_msg.write(VNToogleMsg(true));
+}
+
+fn list_chapters(
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+    chapter_query: Query<Entity, With<ChapterList>>,
+    mut chapter_msg: MessageReader<ChapterMsg>,
+) {
+    let Some(msg) = chapter_msg.read().last() else { return };
+    chapter_query.iter().for_each(|entity| {
+        commands.entity(entity).despawn()
+    });
+    let font = |size: f32| TextFont {
+        font: asset_server.load(FONT).into(),
+        font_size: FontSize::Px(size),
+        ..default()
+    };
+    commands.spawn((
+        Visibility::Visible,
+        ChapterList,
+        RelativeCursorPosition::default(),
+        ZIndex(Z_UI),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(30.),
+            max_height: Val::Percent(90.),
+            left: Val::Percent(13.),
+            top: Val::Percent(1.),
+            padding: UiRect::all(px(12)),
+            row_gap: px(8),
+            flex_direction: FlexDirection::Column,
+            overflow: Overflow::scroll_y(),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0., 0., 0., 0.8)),
+    )).with_children(|parent| {
+        parent.spawn((
+            Text::new(msg.bundle.clone()),
+            font(42.),
+            TextColor(HEADTEXT),
+        ));
+        let chapters = std::iter::once(("From the beginning".to_string(), 0, String::new()))
+            .chain(msg.chapters.iter().map(|c| (c.label.trim_start_matches('*').to_string(), c.index, c.preview.clone())));
+        for (label, index, preview) in chapters {
+            parent.spawn((
+                Button,
+                ChapterButton {
+                    bundle: msg.bundle.clone(),
+                    index,
+                },
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(px(6)),
+                    ..default()
+                },
+                BackgroundColor(Color::NONE),
+            )).with_children(|parent| {
+                parent.spawn((
+                    Text::new(label),
+                    font(32.),
+                    TextColor(LISTTEXT),
+                ));
+                if !preview.is_empty() {
+                    parent.spawn((
+                        Text::new(preview),
+                        font(24.),
+                        TextColor(SELECTTEXT),
+                    ));
+                }
+            });
+        }
+    });
+}
+
+fn choose_chapter(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    mut interaction_query: Query<(
+        &Interaction,
+        &mut BackgroundColor,
+        &ChapterButton,
+    ), (Changed<Interaction>, With<Button>),>,
+    chapter_query: Query<Entity, With<ChapterList>>,
+    mut vn_ui_msg: MessageWriter<VNToogleMsg>,
+    filter: Res<SceneFilter>,
+    mut view_res: ResMut<ViewRes>,
+) {
+    if actions.just_pressed(Action::Exit) && !filter.focused && !view_res.avg {
+        chapter_query.iter().for_each(|entity| {
+            commands.entity(entity).despawn()
+        });
+        return;
+    }
+    let mut start = None;
+    interaction_query.iter_mut().for_each(|(interaction, mut bg_color, button)| {
+        match *interaction {
+            Interaction::Pressed => {
+                start = Some((button.bundle.clone(), button.index));
+            }
+            Interaction::Hovered => {
+                *bg_color = HOVERBG.into();
+            }
+            _ => {
+                *bg_color = Color::NONE.into();
+            }
+        }
+    });
+    if let Some((bundle, index)) = start && let Some(book) = load_book(&bundle, &view_res) {
+        chapter_query.iter().for_each(|entity| {
+            commands.entity(entity).despawn()
+        });
+        start_book(book, index, &mut vn_ui_msg, &mut view_res);
+    }
+}
+
+fn chapter_list_scroll(
+    mut list_query: Query<(&mut ScrollPosition, &ComputedNode, &RelativeCursorPosition), With<ChapterList>>,
+    mut scroll: MessageReader<MouseWheel>,
+) {
+    for ev in scroll.read() {
+        for (mut scroll_pos, node, cursor) in list_query.iter_mut() {
+            if !cursor.cursor_over {
+                continue;
+            }
+            let visible_size = node.size() * node.inverse_scale_factor;
+            let content_size = node.content_size() * node.inverse_scale_factor;
+            let range = (content_size.y - visible_size.y).max(0.);
+            scroll_pos.y = (scroll_pos.y - ev.y * 40.).clamp(0., range);
+        }
+    }
+}
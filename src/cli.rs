@@ -0,0 +1,219 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+// flags followed by a value, including the ones of ExportRequest
+const VALUE_FLAGS: [&str; 11] = [
+    "--profile", "--assets", "--book", "--label", "--skeleton", "--animation", "--size",
+    "--export", "--format", "--fps", "--out",
+];
+const SWITCHES: [&str; 1] = ["--windowed"];
+
+static ASSETS_ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+/// Sets the folder every asset is read from, only the first call has an effect
+pub fn set_assets_root(root: PathBuf) {
+    let _ = ASSETS_ROOT.set(root);
+}
+
+/// `assets` unless `--assets` was given
+pub fn assets_root() -> &'static Path {
+    ASSETS_ROOT.get().map_or(Path::new("assets"), PathBuf::as_path)
+}
+
+/// `path` under the assets root
+pub fn asset_path(path: impl AsRef<Path>) -> PathBuf {
+    assets_root().join(path)
+}
+
+/// Rejects unknown flags, flags given twice and value flags without a value, `args[0]` is the program
+pub fn check_args(args: &[String]) -> Result<(), String> {
+    let mut seen = Vec::new();
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        if seen.contains(&arg) {
+            return Err(format!("{} is given twice", arg));
+        }
+        seen.push(arg);
+        if VALUE_FLAGS.contains(&arg.as_str()) {
+            match rest.next() {
+                Some(value) if !value.starts_with("--") => {}
+                _ => return Err(format!("{} needs a value", arg)),
+            }
+        } else if !SWITCHES.contains(&arg.as_str()) {
+            return Err(format!("unknown argument {}", arg));
+        }
+    }
+    Ok(())
+}
+
+/// The value after `flag`, call check_args first so it is never another flag
+pub fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a String> {
+    args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1))
+}
+
+/// What to open on launch instead of the browser
+#[derive(Clone, Debug, PartialEq)]
+pub enum LaunchTarget {
+    /// A memory book by bundle name or `*.book.json` path, from `label` when given
+    Book {
+        book: String,
+        label: Option<String>,
+    },
+    /// A skeleton bundle playing `animation`, the first animation when None
+    Skeleton {
+        bundle: String,
+        animation: Option<String>,
+    },
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LaunchOptions {
+    pub profile: Option<String>,
+    pub assets: Option<PathBuf>,
+    pub target: Option<LaunchTarget>,
+    pub windowed: bool,
+    // window size, implies windowed
+    pub size: Option<(u32, u32)>,
+}
+
+impl LaunchOptions {
+    /// `[--profile <name>] [--assets <dir>] [--book <name|file.book.json> [--label <label>]]
+    /// [--skeleton <bundle> [--animation <name>]] [--windowed] [--size <w>x<h>]`
+    ///
+    /// `--animation` and `--size` belong to `--export` when it is given.
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        check_args(args)?;
+        let value = |flag: &str| flag_value(args, flag);
+        let exporting = value("--export").is_some();
+        let mut options = Self {
+            profile: value("--profile").cloned(),
+            assets: value("--assets").map(PathBuf::from),
+            windowed: args.iter().any(|a| a == "--windowed"),
+            ..Default::default()
+        };
+        if let Some(assets) = &options.assets && !assets.is_dir() {
+            return Err(format!("assets folder {} not found", assets.display()));
+        }
+        if exporting {
+            if value("--book").or(value("--skeleton")).or(value("--label")).is_some() {
+                return Err("--export can not be used with --book, --skeleton or --label".into());
+            }
+            return Ok(options);
+        }
+        if let Some(flag) = ["--format", "--fps", "--out"].into_iter().find(|flag| value(flag).is_some()) {
+            return Err(format!("{} needs --export", flag));
+        }
+        if value("--label").is_some() && value("--book").is_none() {
+            return Err("--label needs --book".into());
+        }
+        if value("--animation").is_some() && value("--skeleton").is_none() {
+            return Err("--animation needs --skeleton or --export".into());
+        }
+        options.target = match (value("--book"), value("--skeleton")) {
+            (Some(_), Some(_)) => return Err("--book and --skeleton can not be used together".into()),
+            (Some(book), None) => Some(LaunchTarget::Book {
+                book: book.clone(),
+                label: value("--label").cloned(),
+            }),
+            (None, Some(bundle)) => Some(LaunchTarget::Skeleton {
+                bundle: bundle.clone(),
+                animation: value("--animation").cloned(),
+            }),
+            (None, None) => None,
+        };
+        if let Some(size) = value("--size") {
+            let (w, h) = size.split_once('x').ok_or(format!("size {} is not <w>x<h>", size))?;
+            let w = w.parse().map_err(|_| format!("bad width {}", w))?;
+            let h = h.parse().map_err(|_| format!("bad height {}", h))?;
+            if w == 0 || h == 0 {
+                return Err("size must be positive".into());
+            }
+            options.size = Some((w, h));
+            options.windowed = true;
+        }
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<LaunchOptions, String> {
+        let args: Vec<String> = std::iter::once("moe_priest").chain(args.iter().copied()).map(String::from).collect();
+        LaunchOptions::from_args(&args)
+    }
+
+    #[test]
+    fn no_arguments() {
+        let options = parse(&[]).unwrap();
+        assert_eq!(options.target, None);
+        assert!(!options.windowed);
+        assert_eq!(options.size, None);
+    }
+
+    #[test]
+    fn book_from_label() {
+        let options = parse(&["--book", "ch01.book.json", "--label", "start"]).unwrap();
+        assert_eq!(options.target, Some(LaunchTarget::Book {
+            book: "ch01.book.json".into(),
+            label: Some("start".into()),
+        }));
+    }
+
+    #[test]
+    fn skeleton_with_animation() {
+        let options = parse(&["--skeleton", "ch_30005", "--animation", "idle", "--profile", "monmusu"]).unwrap();
+        assert_eq!(options.profile.as_deref(), Some("monmusu"));
+        assert_eq!(options.target, Some(LaunchTarget::Skeleton {
+            bundle: "ch_30005".into(),
+            animation: Some("idle".into()),
+        }));
+    }
+
+    #[test]
+    fn size_implies_windowed() {
+        let options = parse(&["--size", "1280x720"]).unwrap();
+        assert_eq!(options.size, Some((1280, 720)));
+        assert!(options.windowed);
+        assert!(parse(&["--windowed"]).unwrap().windowed);
+    }
+
+    #[test]
+    fn bad_size() {
+        assert!(parse(&["--size", "1280"]).is_err());
+        assert!(parse(&["--size", "0x720"]).is_err());
+        assert!(parse(&["--size", "wide x720"]).is_err());
+    }
+
+    #[test]
+    fn flag_is_not_a_value() {
+        assert!(parse(&["--book", "--windowed"]).is_err());
+        assert!(parse(&["--skeleton"]).is_err());
+    }
+
+    #[test]
+    fn unknown_arguments() {
+        assert!(parse(&["--fullscreen"]).is_err());
+        assert!(parse(&["ch_30005"]).is_err());
+        assert!(parse(&["--windowed", "--windowed"]).is_err());
+    }
+
+    #[test]
+    fn orphan_options() {
+        assert!(parse(&["--label", "start"]).is_err());
+        assert!(parse(&["--animation", "idle"]).is_err());
+        assert!(parse(&["--skeleton", "ch_30005", "--label", "start"]).is_err());
+        assert!(parse(&["--book", "ch01", "--skeleton", "ch_30005"]).is_err());
+    }
+
+    #[test]
+    fn export_options() {
+        let options = parse(&["--export", "ch_30005", "--animation", "idle", "--size", "512x512", "--fps", "30"]).unwrap();
+        assert_eq!(options.target, None);
+        // --size belongs to the export
+        assert!(!options.windowed);
+        assert!(parse(&["--export", "ch_30005", "--book", "ch01"]).is_err());
+        assert!(parse(&["--format", "gif"]).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+use bevy::prelude::*;
+
+use crate::camera::{SkeletonBounds, ViewerFit, fit_transform};
+use crate::game::{AnimeMenuList, HOVERBG, LISTTEXT, SELECTTEXT, ViewRes, spawn_viewer_panels};
+use crate::input_map::{Action, ActionState};
+use crate::skin_panel::SkinSelection;
+use crate::spine_export::ViewerBundle;
+use crate::spine_facade::ViewerSpines;
+
+// viewer skeletons kept side by side, the oldest is replaced after that
+pub(crate) const MAX_COMPARE: usize = 4;
+
+/// Keeps several viewer spines loaded, the AnimeMenuList panels control the ActiveViewer one
+#[derive(Resource, Default)]
+pub(crate) struct CompareMode {
+    pub(crate) enabled: bool,
+    // the other skeletons follow the track times of the active one
+    pub(crate) sync: bool,
+    // viewer spines from left to right
+    pub(crate) order: Vec<Entity>,
+}
+
+/// The viewer spine driven by the animation list, playback control and skin panel
+#[derive(Component)]
+pub(crate) struct ActiveViewer;
+
+#[derive(Component, Clone, Copy)]
+pub(crate) enum CompareButton {
+    Compare,
+    Sync,
+    Next,
+}
+
+pub(crate) struct ComparePlugin;
+
+impl Plugin for ComparePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CompareMode>()
+            .add_systems(Update, (compare_order, compare_input, layout_viewer, compare_panels).chain())
+            // after the animation state is updated for the frame
+            .add_systems(PostUpdate, sync_compare);
+    }
+}
+
+fn compare_order(
+    added: Query<Entity, Added<ViewerBundle>>,
+    viewer_query: Query<(), With<ViewerBundle>>,
+    mut compare: ResMut<CompareMode>,
+) {
+    let stale = compare.order.iter().any(|entity| !viewer_query.contains(*entity));
+    if added.is_empty() && !stale {
+        return;
+    }
+    compare.order.retain(|entity| viewer_query.contains(*entity));
+    compare.order.extend(added.iter());
+}
+
+fn compare_input(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    mut interaction_query: Query<(
+        &Interaction,
+        &CompareButton,
+        &mut TextColor,
+        &mut BackgroundColor,
+    ), (Changed<Interaction>, With<Button>)>,
+    viewer_query: Query<(Entity, Has<ActiveViewer>), With<ViewerBundle>>,
+    mut compare: ResMut<CompareMode>,
+    view_res: Res<ViewRes>,
+) {
+    if view_res.avg {
+        return;
+    }
+    let mut pressed = None;
+    for (interaction, button, mut color, mut bg_color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => pressed = Some(*button),
+            Interaction::Hovered => {
+                *color = SELECTTEXT.into();
+                *bg_color = HOVERBG.into();
+            }
+            _ => {
+                *color = LISTTEXT.into();
+                *bg_color = Color::NONE.into();
+            }
+        }
+    }
+    if actions.just_pressed(Action::Compare) || matches!(pressed, Some(CompareButton::Compare)) {
+        compare.enabled = !compare.enabled;
+        info!("compare mode {}", compare.enabled);
+        if !compare.enabled {
+            // back to the active skeleton alone
+            viewer_query.iter().filter(|(_, active)| !active).for_each(|(entity, _)| {
+                commands.entity(entity).despawn()
+            });
+        }
+    }
+    if actions.just_pressed(Action::SyncTime) || matches!(pressed, Some(CompareButton::Sync)) {
+        compare.sync = !compare.sync;
+        info!("compare sync {}", compare.sync);
+    }
+    if (actions.just_pressed(Action::NextSkeleton) || matches!(pressed, Some(CompareButton::Next)))
+    && compare.order.len() > 1 {
+        let current = compare.order.iter().position(|e| viewer_query.get(*e).is_ok_and(|(_, active)| active));
+        let next = compare.order[current.map_or(0, |i| (i + 1) % compare.order.len())];
+        viewer_query.iter().filter(|(_, active)| *active).for_each(|(entity, _)| {
+            commands.entity(entity).remove::<ActiveViewer>();
+        });
+        commands.entity(next).insert(ActiveViewer);
+    }
+}
+
+// places every viewer spine in its own column once its bounds are known
+fn layout_viewer(
+    mut commands: Commands,
+    changed: Query<(), Changed<SkeletonBounds>>,
+    bounds_query: Query<&SkeletonBounds>,
+    compare: Res<CompareMode>,
+) {
+    if changed.is_empty() && !compare.is_changed() {
+        return;
+    }
+    let placed: Vec<(Entity, Rect)> = compare.order.iter()
+        .filter_map(|&entity| bounds_query.get(entity).ok().map(|bounds| (entity, bounds.0)))
+        .collect();
+    for (column, (entity, bounds)) in placed.iter().enumerate() {
+        let fit = fit_transform(*bounds, column, placed.len());
+        commands.entity(*entity).insert((fit, ViewerFit(fit)));
+    }
+}
+
+// the panels follow the active skeleton, viewer_spine_ready builds them for a new one
+fn compare_panels(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut spines: ViewerSpines,
+    active_query: Query<Entity, Added<ActiveViewer>>,
+    anime_query: Query<Entity, With<AnimeMenuList>>,
+    mut skin_selection: ResMut<SkinSelection>,
+) {
+    // a skeleton without bounds is still loading
+    let Some(entity) = active_query.iter().find(|entity| spines.bounds(*entity).is_some()) else { return };
+    anime_query.iter().for_each(|entity| {
+        commands.entity(entity).despawn()
+    });
+    spawn_viewer_panels(&mut commands, &asset_server, &mut spines, entity, &mut skin_selection);
+}
+
+fn sync_compare(
+    mut spines: ViewerSpines,
+    active_query: Query<Entity, (With<ActiveViewer>, With<ViewerBundle>)>,
+    compare: Res<CompareMode>,
+) {
+    if !compare.enabled || !compare.sync {
+        return;
+    }
+    let Some(active) = active_query.iter().next() else { return };
+    let times = spines.track_times(active);
+    if times.is_empty() {
+        return;
+    }
+    for &entity in compare.order.iter().filter(|entity| **entity != active) {
+        spines.set_track_times(entity, &times);
+    }
+}
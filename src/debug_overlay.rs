@@ -0,0 +1,182 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_spine38::prelude as spine38;
+use bevy_spine42::prelude as spine42;
+
+use crate::camera::VNCamera;
+use crate::game::{FONT, Z_TEXT};
+use crate::input_map::{Action, ActionState};
+use crate::spine_facade::{AnySpine, HasSpine, with_spine};
+use crate::thumbnail::OffscreenSpine;
+
+const DEBUG_BONE: Color = Color::srgb(1., 0.9, 0.3);
+const DEBUG_BOUNDING_BOX: Color = Color::srgb(0.2, 1., 0.2);
+const DEBUG_CLIPPING: Color = Color::srgb(1., 0.2, 0.2);
+const DEBUG_SLOT: Color = Color::srgb(0.3, 0.7, 1.);
+const DEBUG_IK: Color = Color::srgb(0.2, 1., 1.);
+const DEBUG_PATH: Color = Color::srgb(1., 0.3, 1.);
+const DEBUG_PHYSICS: Color = Color::srgb(1., 0.6, 0.2);
+
+/// Draws bones, bounding boxes, clipping polygons and constraint targets of every Spine entity
+#[derive(Resource, Default)]
+pub(crate) struct DebugOverlay {
+    pub(crate) enabled: bool,
+}
+
+/// Name of the slot under the cursor
+#[derive(Component)]
+struct DebugSlotText;
+
+/// SkeletonDebug of either runtime, constraints are (color, target, constrained bones) and slots (name, polygon)
+struct DebugShapes {
+    bones: Vec<(Vec2, Vec2)>,
+    bounding_boxes: Vec<Vec<Vec2>>,
+    clippings: Vec<Vec<Vec2>>,
+    constraints: Vec<(Color, Vec2, Vec<Vec2>)>,
+    slots: Vec<(String, Vec<Vec2>)>,
+}
+
+// both runtimes fill SkeletonDebug with the same fields
+macro_rules! debug_shapes {
+    ($runtime:ident) => {
+        impl From<&$runtime::SkeletonDebug> for DebugShapes {
+            fn from(debug: &$runtime::SkeletonDebug) -> Self {
+                DebugShapes {
+                    bones: debug.bones.iter().map(|bone| (bone.start, bone.end)).collect(),
+                    bounding_boxes: debug.bounding_boxes.clone(),
+                    clippings: debug.clippings.clone(),
+                    constraints: debug.constraints.iter().map(|constraint| {
+                        let color = match constraint.kind {
+                            $runtime::DebugConstraintKind::Ik => DEBUG_IK,
+                            $runtime::DebugConstraintKind::Path => DEBUG_PATH,
+                            $runtime::DebugConstraintKind::Physics => DEBUG_PHYSICS,
+                        };
+                        (color, constraint.target, constraint.bones.clone())
+                    }).collect(),
+                    slots: debug.slots.iter().map(|slot| (slot.name.clone(), slot.polygon.clone())).collect(),
+                }
+            }
+        }
+    };
+}
+
+debug_shapes!(spine38);
+debug_shapes!(spine42);
+
+pub(crate) struct DebugOverlayPlugin;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugOverlay>()
+            .add_systems(Update, (toggle_debug_overlay, draw_spine_debug).chain());
+    }
+}
+
+fn toggle_debug_overlay(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    actions: Res<ActionState>,
+    text_query: Query<Entity, With<DebugSlotText>>,
+    mut overlay: ResMut<DebugOverlay>,
+) {
+    if !actions.just_pressed(Action::DebugOverlay) {
+        return;
+    }
+    overlay.enabled = !overlay.enabled;
+    if overlay.enabled {
+        commands.spawn((
+            DebugSlotText,
+            ZIndex(Z_TEXT),
+            Text::new(""),
+            TextFont {
+                font: asset_server.load(FONT).into(),
+                font_size: FontSize::Px(30.),
+                ..default()
+            },
+            TextColor(DEBUG_SLOT),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(13.),
+                top: Val::Percent(68.),
+                ..default()
+            },
+        ));
+    } else {
+        text_query.iter().for_each(|entity| {
+            commands.entity(entity).despawn()
+        });
+    }
+}
+
+fn draw_spine_debug(
+    spine_query: Query<(AnySpine, &GlobalTransform, &InheritedVisibility), (HasSpine, Without<OffscreenSpine>)>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform), With<VNCamera>>,
+    mut slot_text: Query<&mut Text, With<DebugSlotText>>,
+    mut gizmos: Gizmos,
+    overlay: Res<DebugOverlay>,
+) {
+    if !overlay.enabled {
+        return;
+    }
+    let (camera, camera_transform) = *camera;
+    let cursor = window.cursor_position()
+        .and_then(|pos| camera.viewport_to_world_2d(camera_transform, pos).ok());
+    let mut hovered = None;
+    for (spine, transform, visibility) in spine_query.iter() {
+        if !visibility.get() {
+            continue;
+        }
+        let Some(debug) = with_spine!(spine, |spine, runtime| DebugShapes::from(&runtime::SkeletonDebug::new(&spine.skeleton))) else { continue };
+        let world = |p: Vec2| transform.transform_point(p.extend(0.)).truncate();
+        let outline = |gizmos: &mut Gizmos, polygon: &[Vec2], color: Color| {
+            if polygon.len() > 1 {
+                gizmos.linestrip_2d(polygon.iter().chain(polygon.first()).map(|p| world(*p)), color);
+            }
+        };
+        for (start, end) in &debug.bones {
+            gizmos.line_2d(world(*start), world(*end), DEBUG_BONE);
+            gizmos.circle_2d(world(*start), 4., DEBUG_BONE);
+        }
+        for polygon in &debug.bounding_boxes {
+            outline(&mut gizmos, polygon, DEBUG_BOUNDING_BOX);
+        }
+        for polygon in &debug.clippings {
+            outline(&mut gizmos, polygon, DEBUG_CLIPPING);
+        }
+        for (color, target, bones) in &debug.constraints {
+            let target = world(*target);
+            gizmos.rect_2d(Isometry2d::from_translation(target), Vec2::splat(12.), *color);
+            for bone in bones {
+                gizmos.line_2d(world(*bone), target, color.with_alpha(0.5));
+            }
+        }
+        // the last slot in draw order is on top
+        if let Some(cursor) = cursor {
+            let local = transform.affine().inverse().transform_point3(cursor.extend(0.)).truncate();
+            if let Some((name, polygon)) = debug.slots.iter().rev().find(|(_, polygon)| polygon_contains(polygon, local)) {
+                outline(&mut gizmos, polygon, DEBUG_SLOT);
+                hovered = Some(name.clone());
+            }
+        }
+    }
+    for mut text in slot_text.iter_mut() {
+        let name = hovered.clone().unwrap_or_default();
+        if text.0 != name {
+            text.0 = name;
+        }
+    }
+}
+
+// even-odd rule
+fn polygon_contains(polygon: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    for (i, a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        if (a.y > point.y) != (b.y > point.y)
+        && point.x < a.x + (point.y - a.y) * (b.x - a.x) / (b.y - a.y) {
+            inside = !inside;
+        }
+    }
+    inside
+}
@@ -0,0 +1,247 @@
+use bevy::color::Srgba;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+use std::fs::{File, create_dir_all};
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+use crate::cli::flag_value;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    Png,
+    Apng,
+    Gif,
+}
+
+impl ExportFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "png" => Some(Self::Png),
+            "apng" => Some(Self::Apng),
+            "gif" => Some(Self::Gif),
+            _ => None,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Self::Png => Self::Apng,
+            Self::Apng => Self::Gif,
+            Self::Gif => Self::Png,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ExportSettings {
+    pub format: ExportFormat,
+    pub width: u32,
+    pub height: u32,
+    pub fps: u32,
+    pub out: PathBuf,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::Apng,
+            width: 1024,
+            height: 1024,
+            fps: 30,
+            out: PathBuf::from("export"),
+        }
+    }
+}
+
+/// One animation of a bundle, the first animation when `animation` is None
+#[derive(Clone, Debug)]
+pub struct ExportRequest {
+    pub bundle: String,
+    pub animation: Option<String>,
+    pub settings: ExportSettings,
+}
+
+impl ExportRequest {
+    /// `--export <bundle> [--animation <name>] [--format png|apng|gif] [--size <w>x<h>] [--fps <n>] [--out <dir>]`
+    pub fn from_args(args: &[String]) -> Result<Option<Self>, String> {
+        let Some(i) = args.iter().position(|a| a == "--export") else { return Ok(None) };
+        let bundle = args.get(i + 1).ok_or("--export needs a bundle name")?.clone();
+        let mut request = Self {
+            bundle,
+            animation: None,
+            settings: ExportSettings::default(),
+        };
+        let value = |flag: &str| flag_value(args, flag);
+        request.animation = value("--animation").cloned();
+        if let Some(format) = value("--format") {
+            request.settings.format = ExportFormat::parse(format).ok_or(format!("unknown format {}", format))?;
+        }
+        if let Some(size) = value("--size") {
+            let (w, h) = size.split_once('x').ok_or(format!("size {} is not <w>x<h>", size))?;
+            request.settings.width = w.parse().map_err(|_| format!("bad width {}", w))?;
+            request.settings.height = h.parse().map_err(|_| format!("bad height {}", h))?;
+        }
+        if let Some(fps) = value("--fps") {
+            request.settings.fps = fps.parse().map_err(|_| format!("bad fps {}", fps))?;
+        }
+        if let Some(out) = value("--out") {
+            request.settings.out = PathBuf::from(out);
+        }
+        if request.settings.width == 0 || request.settings.height == 0 || request.settings.fps == 0 {
+            return Err("size and fps must be positive".into());
+        }
+        Ok(Some(request))
+    }
+
+    fn name(&self, animation: &str) -> String {
+        format!("{}_{}", self.bundle, animation).replace(['/', '\\'], "_")
+    }
+
+    /// Writes RGBA8 frames with straight alpha, returns the written file or folder
+    pub fn write(&self, animation: &str, frames: Vec<Vec<u8>>) -> Result<PathBuf, String> {
+        let ExportSettings { format, width, height, fps, ref out } = self.settings;
+        create_dir_all(out).map_err(|e| e.to_string())?;
+        match format {
+            ExportFormat::Png => {
+                let dir = out.join(self.name(animation));
+                create_dir_all(&dir).map_err(|e| e.to_string())?;
+                for (i, frame) in frames.into_iter().enumerate() {
+                    let image = RgbaImage::from_raw(width, height, frame).ok_or("frame size mismatch")?;
+                    image.save(dir.join(format!("{:05}.png", i))).map_err(|e| e.to_string())?;
+                }
+                Ok(dir)
+            }
+            ExportFormat::Apng => {
+                let path = out.join(format!("{}.png", self.name(animation)));
+                let file = File::create(&path).map_err(|e| e.to_string())?;
+                let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+                encoder.set_color(png::ColorType::Rgba);
+                encoder.set_depth(png::BitDepth::Eight);
+                // 0 plays forever
+                encoder.set_animated(frames.len() as u32, 0).map_err(|e| e.to_string())?;
+                encoder.set_frame_delay(1, fps as u16).map_err(|e| e.to_string())?;
+                let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+                for frame in frames {
+                    writer.write_image_data(&frame).map_err(|e| e.to_string())?;
+                }
+                writer.finish().map_err(|e| e.to_string())?;
+                Ok(path)
+            }
+            ExportFormat::Gif => {
+                let path = out.join(format!("{}.gif", self.name(animation)));
+                let file = File::create(&path).map_err(|e| e.to_string())?;
+                let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), 10);
+                encoder.set_repeat(Repeat::Infinite).map_err(|e| e.to_string())?;
+                for frame in frames {
+                    let image = RgbaImage::from_raw(width, height, frame).ok_or("frame size mismatch")?;
+                    let delay = Delay::from_numer_denom_ms(1000, fps);
+                    encoder.encode_frame(Frame::from_parts(image, 0, 0, delay)).map_err(|e| e.to_string())?;
+                }
+                Ok(path)
+            }
+        }
+    }
+}
+
+/// Turns sRGB pixels rendered with normal blending onto a transparent target into straight alpha.
+/// Blending happens in linear space, so the color is divided by alpha there.
+pub fn unpremultiply(pixels: &mut [u8]) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        let alpha = pixel[3] as f32 / 255.;
+        if alpha == 0. || alpha == 1. {
+            continue;
+        }
+        for c in &mut pixel[..3] {
+            let linear = (Srgba::gamma_function(*c as f32 / 255.) / alpha).min(1.);
+            *c = (Srgba::gamma_function_inverse(linear) * 255.).round() as u8;
+        }
+    }
+}
+
+/// Drops the row padding of a GPU readback, rows are aligned to 256 bytes
+pub fn unpad_rows(data: &[u8], width: u32, height: u32) -> Vec<u8> {
+    let row = width as usize * 4;
+    let padded = row.div_ceil(256) * 256;
+    if padded == row {
+        return data[..row * height as usize].to_vec();
+    }
+    data.chunks(padded).take(height as usize).flat_map(|r| &r[..row]).copied().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<ExportRequest>, String> {
+        let args: Vec<String> = std::iter::once("moe_priest").chain(args.iter().copied()).map(String::from).collect();
+        ExportRequest::from_args(&args)
+    }
+
+    #[test]
+    fn no_export() {
+        assert!(parse(&["--skeleton", "ch_30005"]).unwrap().is_none());
+    }
+
+    #[test]
+    fn defaults() {
+        let request = parse(&["--export", "ch_30005"]).unwrap().unwrap();
+        assert_eq!(request.bundle, "ch_30005");
+        assert_eq!(request.animation, None);
+        assert_eq!(request.settings.format, ExportFormat::Apng);
+        assert_eq!((request.settings.width, request.settings.height, request.settings.fps), (1024, 1024, 30));
+        assert_eq!(request.settings.out, PathBuf::from("export"));
+    }
+
+    #[test]
+    fn all_options() {
+        let request = parse(&[
+            "--export", "ch_30005", "--animation", "idle", "--format", "GIF",
+            "--size", "640x480", "--fps", "24", "--out", "clips",
+        ]).unwrap().unwrap();
+        assert_eq!(request.animation.as_deref(), Some("idle"));
+        assert_eq!(request.settings.format, ExportFormat::Gif);
+        assert_eq!((request.settings.width, request.settings.height, request.settings.fps), (640, 480, 24));
+        assert_eq!(request.settings.out, PathBuf::from("clips"));
+    }
+
+    #[test]
+    fn bad_options() {
+        assert!(parse(&["--export"]).is_err());
+        assert!(parse(&["--export", "ch_30005", "--format", "webp"]).is_err());
+        assert!(parse(&["--export", "ch_30005", "--size", "640"]).is_err());
+        assert!(parse(&["--export", "ch_30005", "--size", "0x480"]).is_err());
+        assert!(parse(&["--export", "ch_30005", "--fps", "0"]).is_err());
+        assert!(parse(&["--export", "ch_30005", "--fps", "fast"]).is_err());
+    }
+
+    #[test]
+    fn unpad_aligned_rows() {
+        // 64 pixels make a 256 byte row, nothing to drop
+        let data: Vec<u8> = (0..64 * 4 * 2).map(|i| i as u8).collect();
+        assert_eq!(unpad_rows(&data, 64, 2), data);
+    }
+
+    #[test]
+    fn unpad_padded_rows() {
+        let mut data = vec![0; 256 * 2];
+        data[..8].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        data[256..264].copy_from_slice(&[9, 10, 11, 12, 13, 14, 15, 16]);
+        assert_eq!(unpad_rows(&data, 2, 2), (1..=16).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn unpremultiply_keeps_opaque_and_transparent() {
+        let mut pixels = vec![10, 20, 30, 255, 40, 50, 60, 0];
+        unpremultiply(&mut pixels);
+        assert_eq!(pixels, [10, 20, 30, 255, 40, 50, 60, 0]);
+    }
+
+    #[test]
+    fn unpremultiply_in_linear_space() {
+        // linear 1.0 and 0.5 channels blended at half alpha onto transparent black
+        let mut pixels = vec![188, 0, 137, 128];
+        unpremultiply(&mut pixels);
+        assert_eq!(pixels, [255, 0, 187, 128]);
+    }
+}
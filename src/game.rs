@@ -2,115 +2,159 @@
 // use bevy::window::PresentMode;
 
 use bevy::audio::{PlaybackMode, Volume};
+use bevy::camera::visibility::RenderLayers;
 use bevy::input::mouse::{MouseMotion, MouseWheel};
 use bevy::prelude::*;
 use bevy::sprite::Anchor;
+use bevy::ui::RelativeCursorPosition;
 use bevy::ui_widgets::{ControlOrientation, Scrollbar, ScrollbarThumb};
-use bevy::window::{PrimaryWindow, WindowMode, WindowResolution};
-use bevy_auto_scaling::{AspectRatio, ScalePlugin, ScalingUI, fixed_size_2d};
-use bevy_spine::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_auto_scaling::{ScalePlugin, ScalingUI};
+use bevy_spine38::prelude as spine38;
+use bevy_spine42::prelude as spine42;
 use bevy_transform_interpolation::prelude::*;
-use bevy_tweening::{AnimTarget, Lens, TweenAnim, TweenState, TweeningPlugin, lens::*};
+use bevy_tweening::{AnimTarget, TweenAnim, TweenState, TweeningPlugin, lens::*};
 use regex::{Regex, Captures};
 use std::collections::{BTreeMap, HashMap};
 use std::fs::read_to_string;
+use std::sync::LazyLock;
 use std::time::Duration;
 
-use crate::utage4::{self, VNConfig};
+use crate::anime_control::{AnimeControl, AnimeControlPlugin, spawn_anime_control};
+use crate::audio_path::AudioPaths;
+use crate::camera::{GUI_LAYER, SkeletonBounds, VNCamera, reset_camera, reset_view, spawn_cameras, zoom_cmd};
+use crate::cg_gallery::{CgGallery, CgGalleryPlugin, CgView, cg_category, open_cg, spawn_cg_cells, unlock_cg};
+use crate::chapter::{ChapterList, ChapterMsg, ChapterPlugin, book_chapters, load_book, start_book};
+use crate::cli::{LaunchOptions, LaunchTarget, asset_path, assets_root};
+use crate::compare::{ActiveViewer, CompareMode, ComparePlugin, MAX_COMPARE};
+use crate::debug_overlay::DebugOverlayPlugin;
+use crate::gamepad::GamepadPlugin;
+use crate::image_effect::{ImageEffect, ImageEffectPlugin, image_effect_cmd};
+use crate::input_map::{Action, ActionState, InputMapPlugin};
+use crate::intro_loop::{IntroLoop, IntroLoopPlugin};
+use crate::mixer::{AudioMixer, MixerPlugin};
+use crate::profile::profile;
+use crate::scene_filter::{SceneFilter, SceneFilterPlugin, SearchBox, SearchText};
+use crate::settings::{LaunchWindow, MessageWindow, SettingsPlugin, UserSettings};
+use crate::skin_panel::{SkinPanel, SkinPanelPlugin, SkinSelection, spawn_skin_panel};
+use crate::spine_export::{SpineExportPlugin, ViewerBundle};
+use crate::spine_facade::{AnySpine, HasSpine, SkeletonAssets, SkeletonReadyMsg, SkeletonSet, SpineFacadePlugin, ViewerSpines, with_spine};
+use crate::spine_tween::{ShakeAnim, SpineColorLens38, SpineColorLens42, shake_anim, spawn_shake};
+use crate::spine_version;
+use crate::thumbnail::{OffscreenSpine, SceneMenuScroll, SceneThumb, ThumbnailPlugin};
+use crate::transition::{RuleFade, TransitionPlugin, rule_fade_cmd};
 use crate::tween::{Tween, TweenType};
+use crate::utage4::{self, VNConfig};
 
-include!("spine_tween.rs");
-
-const FONT: &str = "FOT-NewRodinProN-EB.otf";
-const HEADTEXT: Color = Color::srgb(0.5, 0.8, 0.7);
-const LISTTEXT: Color = Color::srgb(0.2, 0.8, 0.2);
-const SELECTTEXT: Color = Color::srgb(0.8, 0.8, 0.8);
-const HOVERBG: Color = Color::srgb(0.1, 0.4, 0.1);
-const VNSPEED: Duration = Duration::from_millis(60);
-const AUTOFORWARD: Duration = Duration::from_millis(1000);
-const Z_CG: i32 = 300;
-const Z_UI: i32 = 993;
-const Z_TEXT: i32 = 996;
-const Z_FADE: i32 = 999;
+pub(crate) const FONT: &str = "FOT-NewRodinProN-EB.otf";
+pub(crate) const HEADTEXT: Color = Color::srgb(0.5, 0.8, 0.7);
+pub(crate) const LISTTEXT: Color = Color::srgb(0.2, 0.8, 0.2);
+pub(crate) const SELECTTEXT: Color = Color::srgb(0.8, 0.8, 0.8);
+pub(crate) const HOVERBG: Color = Color::srgb(0.1, 0.4, 0.1);
+// defaults of the text speed and auto-forward settings
+pub(crate) const VNSPEED: Duration = Duration::from_millis(60);
+pub(crate) const AUTOFORWARD: Duration = Duration::from_millis(1000);
+pub(crate) const Z_CG: i32 = 300;
+pub(crate) const Z_UI: i32 = 993;
+pub(crate) const Z_TEXT: i32 = 996;
+pub(crate) const Z_FADE: i32 = 999;
 const BG_SCALE: f32 = 1.725;
 const EVENT_SCALE: f32 = 1.35;
 const SPRITE_SCALE: f32 = 1.;
 const SPINE_SCALE: f32 = 1.5;
+// left edge of the scene list in window width ratio
+pub(crate) const LIST_LEFT: f32 = 0.64;
 
 macro_rules! str {
     ($var:expr) => { $var.as_deref().unwrap_or("") };
     ($var:expr, $default:expr) => { $var.as_deref().unwrap_or($default) };
 }
+pub(crate) use str;
 
 macro_rules! f32 {
     ($var:ident = $source:expr, $default:expr) => {
-        let $var = str!($source, stringify!($default)).parse::<f32>().unwrap_or($default);
+        let $var = $crate::game::str!($source, stringify!($default)).parse::<f32>().unwrap_or($default);
     };
     ($var:ident, $default:expr) => {
         $var.parse::<f32>().unwrap_or($default)
     };
 }
+pub(crate) use f32;
 
+// folders under the asset roots of the GameProfile
 macro_rules! define_paths {
-    ($root:literal, $(($name:ident, $subpath:literal)),*) => {
+    ($root:ident, $(($name:ident, $subpath:literal)),*) => {
         $(
-            const $name: &str = concat!($root, $subpath);
+            pub(crate) static $name: LazyLock<String> = LazyLock::new(|| format!("{}{}", profile().$root, $subpath));
         )*
     };
 }
 
 define_paths! {
-    "advscene/resources/advscene/sound/",
+    sound,
     (BGM, "bgm/"),
     (SE, "se/"),
     (VOICE, "voice/")
 }
 
 define_paths! {
-    "advscene/resources/advscene/texture/",
+    texture,
     (BG, "bg/"),
     (EVENT, "event/"),
-    (SPRITE, "sprite/")
+    (RULE, "rule/"),
+    (SPRITE, "sprite/"),
+    (THUMBNAIL, "thumbnail/")
+}
+
+fn srgb_u8([r, g, b]: [u8; 3]) -> Color {
+    Color::srgb_u8(r, g, b)
 }
 
-#[derive(Debug)]
-struct Location {
-    path: String,
-    name: String,
-    ext: String,
+#[derive(Clone, Debug)]
+pub(crate) struct Location {
+    pub(crate) path: String,
+    pub(crate) name: String,
+    pub(crate) ext: String,
+    // Spine version read from the skeleton, None for memory books
+    pub(crate) version: Option<String>,
 }
 
 #[derive(Clone, PartialEq)]
-enum ListMode {
+pub(crate) enum ListMode {
     Gallery,
     Motion,
     Memory,
+    Cg,
 }
 
 #[derive(Resource)]
-struct ViewRes {
-    spines: BTreeMap<String, Location>,
-    events: BTreeMap<String, Location>,
-    mode: ListMode,
-    vn: VNConfig,
-    avg: bool,
-    avg_nodes: Vec<utage4::Node>,
-    avg_offset: usize,
+pub(crate) struct ViewRes {
+    pub(crate) spines: BTreeMap<String, Location>,
+    pub(crate) events: BTreeMap<String, Location>,
+    pub(crate) mode: ListMode,
+    pub(crate) vn: VNConfig,
+    pub(crate) avg: bool,
+    pub(crate) avg_nodes: Vec<utage4::Node>,
+    pub(crate) avg_offset: usize,
     avg_regex: Regex,
-    fast: bool,
+    pub(crate) fast: bool,
     auto: bool,
+    // SkipOff forbids skipping even with Ctrl held
+    pub(crate) skip_forbidden: bool,
+    // HideMessageWindow hides the window until ShowMessageWindow
+    pub(crate) window_hidden: bool,
     voice_played: bool,
     forwarded: bool,
     spine_cache: Vec<Entity>,
-    wait_timer: Option<Timer>,
+    pub(crate) wait_timer: Option<Timer>,
     pending_effects: u32,
-    effect_wait: bool,
-    params: HashMap<String, String>,
-    avg_labels: HashMap<String, usize>,
-    selection: Option<SelectionState>,
+    pub(crate) effect_wait: bool,
+    pub(crate) params: HashMap<String, String>,
+    pub(crate) avg_labels: HashMap<String, usize>,
+    pub(crate) selection: Option<SelectionState>,
 }
 
-struct SelectionState {
+pub(crate) struct SelectionState {
     labels: Vec<String>,
     texts: Vec<String>,
     index: usize,
@@ -128,10 +172,11 @@ struct SelectionItem(usize);
 struct SceneMenuList;
 
 #[derive(Component)]
-struct SceneMenu;
+// bundle name
+pub(crate) struct SceneMenu(pub(crate) String);
 
 #[derive(Component)]
-struct AnimeMenuList;
+pub(crate) struct AnimeMenuList;
 
 #[derive(Component)]
 struct AnimeMenu;
@@ -143,10 +188,10 @@ struct ModeMenu;
 struct VNChar;
 
 #[derive(Component)]
-struct VNText {
+pub(crate) struct VNText {
     text: String,
     index: usize,
-    timer: Timer,
+    pub(crate) timer: Timer,
 }
 
 impl VNText {
@@ -158,14 +203,15 @@ impl VNText {
         }
     }
 
-    fn len(&self) -> usize {
+    pub(crate) fn len(&self) -> usize {
         self.text.chars().count()
     }
 
-    fn update(&mut self, text: &str) {
+    pub(crate) fn update(&mut self, text: &str) {
         self.text = text.into();
         self.index = 0;
-        self.timer = Timer::new(VNSPEED, TimerMode::Repeating);
+        // the speed is set by apply_settings
+        self.timer = Timer::new(self.timer.duration(), TimerMode::Repeating);
     }
 
     fn skip_to_end(&mut self) {
@@ -208,9 +254,9 @@ impl FadeOverlay {
 
 // marker for effects that require waiting (WaitType != NoWait)
 #[derive(Component)]
-struct WaitEffect;
+pub(crate) struct WaitEffect;
 
-fn should_wait(wait_type: &Option<String>) -> bool {
+pub(crate) fn should_wait(wait_type: &Option<String>) -> bool {
     !wait_type.as_deref().map(str::trim).is_some_and(|s| s == "NoWait")
 }
 
@@ -219,9 +265,10 @@ fn count_effects(
     tween_query: Query<(Entity, &TweenAnim), With<WaitEffect>>,
     shake_query: Query<(), (With<ShakeAnim>, With<WaitEffect>)>,
     fade_query: Query<(), (With<FadeOverlay>, With<WaitEffect>)>,
+    rule_query: Query<(), (With<RuleFade>, With<WaitEffect>)>,
     mut view_res: ResMut<ViewRes>,
 ) {
-    let mut pending = (shake_query.iter().count() + fade_query.iter().count()) as u32;
+    let mut pending = (shake_query.iter().count() + fade_query.iter().count() + rule_query.iter().count()) as u32;
     for (entity, anim) in tween_query.iter() {
         if anim.tween_state() == TweenState::Completed {
             commands.entity(entity).remove::<WaitEffect>();
@@ -233,21 +280,21 @@ fn count_effects(
 }
 
 #[derive(PartialEq)]
-enum TextureType {
+pub(crate) enum TextureType {
     Bg,
     Event,
     Sprite,
 }
 
 #[derive(Component)]
-struct AvgTransform {
+pub(crate) struct AvgTransform {
     orig: Transform,
     avg: Transform,
 }
 
 #[derive(Component)]
 // category, label, layer, scale, avg_info
-struct VNTexture(TextureType, String, String, f32, AvgTransform);
+pub(crate) struct VNTexture(pub(crate) TextureType, pub(crate) String, pub(crate) String, pub(crate) f32, pub(crate) AvgTransform);
 
 impl VNTexture {
     fn move_to(&mut self, to: Vec3, update: bool) -> Vec3 {
@@ -285,7 +332,7 @@ impl VNTexture {
 
 #[derive(Component)]
 // label, animation, layer, avg_info
-struct VNSpine(String, String, String, AvgTransform);
+pub(crate) struct VNSpine(pub(crate) String, pub(crate) String, pub(crate) String, pub(crate) AvgTransform);
 
 impl VNSpine {
     fn move_to(&mut self, to: Vec3, update: bool) -> Vec3 {
@@ -322,7 +369,7 @@ impl VNSpine {
 }
 
 #[derive(PartialEq)]
-enum AudioType {
+pub(crate) enum AudioType {
     Bgm,
     Se,
     Ambience,
@@ -331,32 +378,50 @@ enum AudioType {
 
 #[derive(Component)]
 // category, label
-struct VNAudio(AudioType, String);
+pub(crate) struct VNAudio(pub(crate) AudioType, pub(crate) String);
 
 #[derive(Component)]
-struct AudioFade(Timer, Volume);
+pub(crate) struct AudioFade(pub(crate) Timer, pub(crate) Volume);
 
 #[derive(Component)]
-struct VNGui;
+pub(crate) struct VNGui;
 
 #[derive(Message)]
 struct SceneMsg(ListMode);
 
 #[derive(Message)]
-struct VNToogleMsg(bool);
+pub(crate) struct VNToogleMsg(pub(crate) bool);
 
 #[derive(Message)]
 struct VNMsg;
 
-pub fn play() {
+/// The book or skeleton given on the command line, opened by `launch`
+#[derive(Resource)]
+struct Launch(Option<LaunchTarget>);
+
+/// Animation of the skeleton given on the command line, played once it is ready
+#[derive(Resource)]
+struct LaunchAnimation(String);
+
+pub fn play(options: LaunchOptions) {
+    let settings = UserSettings::load();
+    let launch_window = LaunchWindow {
+        windowed: options.windowed,
+        size: options.size.map(|(width, height)| [width, height]),
+    };
     App::new()
         .add_plugins((
             DefaultPlugins.set(
+                AssetPlugin {
+                    file_path: assets_root().to_string_lossy().into(),
+                    ..default()
+                }
+            ).set(
                 WindowPlugin {
                     primary_window: Some(Window {
-                        mode: WindowMode::BorderlessFullscreen(MonitorSelection::Current),
+                        mode: settings.window_mode(&launch_window),
                         // present_mode: PresentMode::Immediate,
-                        resolution: WindowResolution::new(3840, 2160).with_scale_factor_override(1.),
+                        resolution: settings.window_resolution(&launch_window),
                         ..default()
                     }),
                     ..default()
@@ -364,11 +429,35 @@ pub fn play() {
             ),
             // FrameTimeDiagnosticsPlugin::default(),
             // LogDiagnosticsPlugin::default(),
+            ImageEffectPlugin,
+            IntroLoopPlugin,
+            MixerPlugin,
             ScalePlugin,
-            SpinePlugin,
+            spine38::SpineAudioPlugin,
+            spine42::SpineAudioPlugin,
+            SpineFacadePlugin,
             TransformInterpolationPlugin::interpolate_all(),
+            TransitionPlugin,
             TweeningPlugin,
         ))
+        // viewer tools
+        .add_plugins((
+            AnimeControlPlugin,
+            CgGalleryPlugin,
+            ChapterPlugin,
+            ComparePlugin,
+            DebugOverlayPlugin,
+            GamepadPlugin,
+            InputMapPlugin,
+            SceneFilterPlugin,
+            SettingsPlugin,
+            SkinPanelPlugin,
+            SpineExportPlugin,
+            ThumbnailPlugin,
+        ))
+        .insert_resource(settings)
+        .insert_resource(launch_window)
+        .insert_resource(Launch(options.target))
         .insert_resource(ClearColor(Color::NONE))
         .insert_resource(Time::<Fixed>::from_hz(30.))
         .insert_resource(ScalingUI {
@@ -378,13 +467,14 @@ pub fn play() {
         .add_message::<SceneMsg>()
         .add_message::<VNToogleMsg>()
         .add_message::<VNMsg>()
-        .add_systems(Startup, setup)
+        .add_systems(Startup, (setup, launch).chain())
         .add_systems(Update, (
             count_effects.before(check_wait),
             toggle_fullscreeen,
             list_scene,
             choose_scene,
-            spine_spawn.in_set(SpineSet::OnReady),
+            spine_spawn.in_set(SkeletonSet::OnReady),
+            (viewer_spine_ready, launch_animation).chain().in_set(SkeletonSet::OnReady),
             choose_animation,
             choose_mode,
             input_handler,
@@ -395,6 +485,7 @@ pub fn play() {
             fade_sound,
             check_wait,
             check_auto_forward,
+            reset_view,
         ))
         .add_systems(FixedUpdate, (mouse_scroll, mouse_object_move, play_vn))
         .run();
@@ -405,33 +496,15 @@ fn setup(
     mut commands: Commands,
     mut scene_msg: MessageWriter<SceneMsg>,
 ) {
-    let vn = if let Ok(content) = read_to_string("assets/advscene/scenariochapter/config.chapter.json") {
+    let vn = if let Ok(content) = read_to_string(asset_path("advscene/scenariochapter/config.chapter.json")) {
         VNConfig::new(content)
     } else {
         VNConfig::default()
     };
 
-    let mut spines = BTreeMap::new();
-    if let Ok(content) = read_to_string("assets/spine.txt") {
-        for spine in content.lines() {
-            if let (Some(l), Some(r)) = (spine.rfind('/'), spine.rfind('.'))
-                && l < r {
-                    let path = spine[..l].to_string();
-                    if let Some(rr) = path.rfind('/') {
-                        let key = path[rr+1..].to_string();
-                        let name = spine[l+1..r].to_string();
-                        let ext = spine[r+1..].to_string();
-                        spines.insert(key, Location {
-                            path,
-                            name,
-                            ext,
-                        });
-                    }
-                }
-        }
-    }
+    let spines = load_spines();
     let mut events = BTreeMap::new();
-    if let Ok(content) = read_to_string("assets/memory.txt") {
+    if let Ok(content) = read_to_string(asset_path("memory.txt")) {
         for event in content.lines() {
             if let (Some(l), Some(r)) = (event.rfind('/'), event.find('.')) && l < r {
                 let path = event[..l].to_string();
@@ -441,16 +514,13 @@ fn setup(
                     path,
                     name,
                     ext,
+                    version: None,
                 });
             }
         }
     }
 
-    commands.spawn((
-        Camera2d,
-        AspectRatio(16. / 9.),
-        fixed_size_2d(1920. * 1.14514, 1080. * 1.14514),
-    ));
+    spawn_cameras(&mut commands);
     commands.insert_resource(ViewRes {
         spines,
         events,
@@ -463,6 +533,8 @@ fn setup(
         avg_regex: Regex::new(r"<interval=(?P<interval>[^>]*)>|<param=(?P<param>[^>]*)>|(?P<other><[^>]*>)").unwrap(),
         fast: false,
         auto: false,
+        skip_forbidden: false,
+        window_hidden: false,
         voice_played: false,
         forwarded: false,
         spine_cache: vec!(),
@@ -473,6 +545,7 @@ fn setup(
         avg_labels: HashMap::new(),
         selection: None,
     });
+    commands.insert_resource(AudioPaths::new(&assets_root().to_string_lossy(), &profile().audio_extensions));
 
     commands.spawn((
         Visibility::Visible,
@@ -488,7 +561,7 @@ fn setup(
             ..default()
         },
     )).with_children(|parent| {
-        for m in ["Gallery", "Motion", "Memory"] {
+        for m in ["Gallery", "Motion", "Memory", "CG"] {
             parent.spawn((
                 Button,
                 Text::new(m),
@@ -506,7 +579,7 @@ fn setup(
     scene_msg.write(SceneMsg(ListMode::Gallery));
     commands.spawn((
         AudioPlayer::new(
-            asset_server.load(format!("{}{}", VOICE, get_intro()))
+            asset_server.load(format!("{}{}", *VOICE, profile().intro_voice()))
         ),
         PlaybackSettings {
             mode: PlaybackMode::Despawn,
@@ -514,11 +587,18 @@ fn setup(
         },
     ));
 
+    let layout = &profile().message_window;
     commands.spawn((
         Visibility::Hidden,
         VNGui,
-        get_adv_ui(&asset_server),
-        get_adv_transform(),
+        MessageWindow(layout.alpha),
+        RenderLayers::layer(GUI_LAYER),
+        Sprite {
+            image: asset_server.load(&layout.image),
+            color: Color::srgba(1., 1., 1., layout.alpha),
+            ..default()
+        },
+        Transform::from_translation(Vec3::new(0., layout.y, Z_UI as f32)),
     ));
     commands.spawn((
         Visibility::Hidden,
@@ -528,13 +608,14 @@ fn setup(
         Anchor::CENTER_LEFT,
         VNGui,
         VNChar,
+        RenderLayers::layer(GUI_LAYER),
         TextFont {
-            font: asset_server.load(ADVFONT).into(),
+            font: asset_server.load(&profile().font).into(),
             font_size: FontSize::Px(136.),
             ..default()
         },
-        TextColor(CHARTEXT),
-        Transform::from_translation(Vec3::new(-572., -366., (Z_UI + 1) as f32)).with_scale(Vec3::ONE / 4.),
+        TextColor(srgb_u8(profile().palette.char_text)),
+        Transform::from_translation(Vec2::from(layout.name).extend((Z_UI + 1) as f32)).with_scale(Vec3::ONE / 4.),
     ));
     commands.spawn((
         Visibility::Hidden,
@@ -544,49 +625,144 @@ fn setup(
         Anchor::TOP_LEFT,
         VNGui,
         VNText::new(),
+        RenderLayers::layer(GUI_LAYER),
         TextFont {
-            font: asset_server.load(ADVFONT).into(),
+            font: asset_server.load(&profile().font).into(),
             font_size: FontSize::Px(136.),
             ..default()
         },
-        TextColor(VNTEXT),
-        Transform::from_translation(Vec3::new(-550., -420., (Z_UI + 1) as f32)).with_scale(Vec3::ONE / 4.),
+        TextColor(srgb_u8(profile().palette.vn_text)),
+        Transform::from_translation(Vec2::from(layout.text).extend((Z_UI + 1) as f32)).with_scale(Vec3::ONE / 4.),
     ));
 }
 
-fn toggle_fullscreeen(
-    key: Res<ButtonInput<KeyCode>>,
-    mut window: Single<&mut Window, With<PrimaryWindow>>,
+fn launch(
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+    spine_query: Query<Entity, (HasSpine, Without<OffscreenSpine>)>,
+    mut skeletons: SkeletonAssets,
+    mut scene_msg: MessageWriter<SceneMsg>,
+    mut vn_ui_msg: MessageWriter<VNToogleMsg>,
+    launch: Res<Launch>,
+    cg: Res<CgGallery>,
+    compare: Res<CompareMode>,
+    mut view_res: ResMut<ViewRes>,
 ) {
-    if (key.just_released(KeyCode::Enter) && (key.pressed(KeyCode::AltLeft) || key.pressed(KeyCode::AltRight)))
-    || key.just_released(KeyCode::F11) {
-        if window.mode == WindowMode::Windowed {
-            window.mode = WindowMode::BorderlessFullscreen(MonitorSelection::Current);
-        } else {
-            window.mode = WindowMode::Windowed;
+    match &launch.0 {
+        Some(LaunchTarget::Book { book, label }) => {
+            // a bundle listed in memory.txt or a book file
+            let Some(nodes) = load_book(book, &view_res).or_else(|| read_to_string(book).ok().map(utage4::parse_book)) else {
+                error!("book not found: {}", book);
+                return;
+            };
+            let index = label.as_ref().map_or(0, |label| {
+                let label = format!("*{}", label.trim_start_matches('*'));
+                nodes.iter().position(|n| n.command.as_deref() == Some(&label)).unwrap_or_else(|| {
+                    warn!("label {} not found in {}", label, book);
+                    0
+                })
+            });
+            view_res.mode = ListMode::Memory;
+            scene_msg.write(SceneMsg(ListMode::Memory));
+            start_book(nodes, index, &mut vn_ui_msg, &mut view_res);
+        }
+        Some(LaunchTarget::Skeleton { bundle, animation }) => {
+            if !view_res.spines.contains_key(bundle) {
+                error!("bundle not found: {}", bundle);
+                return;
+            }
+            let mode = if bundle.starts_with("r18") { ListMode::Gallery } else { ListMode::Motion };
+            view_res.mode = mode.clone();
+            scene_msg.write(SceneMsg(mode));
+            open_scene(bundle, &asset_server, &mut commands, &spine_query, &mut skeletons, &mut vn_ui_msg, &cg, &compare, &mut view_res);
+            if let Some(animation) = animation {
+                commands.insert_resource(LaunchAnimation(animation.clone()));
+            }
+        }
+        None => {}
+    }
+}
+
+fn launch_animation(
+    mut commands: Commands,
+    mut spines: ViewerSpines,
+    active_query: Query<(), With<ActiveViewer>>,
+    mut skeleton_ready_msg: MessageReader<SkeletonReadyMsg>,
+    animation: Option<Res<LaunchAnimation>>,
+    control: Res<AnimeControl>,
+) {
+    let Some(animation) = animation else { return };
+    for msg in skeleton_ready_msg.read() {
+        if !active_query.contains(msg.entity) {
+            continue;
+        }
+        if !spines.set_animation(msg.entity, control.track, &animation.0, control.looping, control.mix) {
+            warn!("animation not found: {}", animation.0);
+        }
+        commands.remove_resource::<LaunchAnimation>();
+    }
+}
+
+pub(crate) fn load_spines() -> BTreeMap<String, Location> {
+    let mut spines = BTreeMap::new();
+    if let Ok(content) = read_to_string(asset_path("spine.txt")) {
+        for spine in content.lines() {
+            if let (Some(l), Some(r)) = (spine.rfind('/'), spine.rfind('.'))
+                && l < r {
+                    let path = spine[..l].to_string();
+                    if let Some(rr) = path.rfind('/') {
+                        let key = path[rr+1..].to_string();
+                        let name = spine[l+1..r].to_string();
+                        let ext = spine[r+1..].to_string();
+                        let version = spine_version::detect(&asset_path(spine));
+                        spines.insert(key, Location {
+                            path,
+                            name,
+                            ext,
+                            version,
+                        });
+                    }
+                }
         }
     }
+    spines
+}
+
+// saved with the settings, apply_settings changes the window
+fn toggle_fullscreeen(
+    actions: Res<ActionState>,
+    mut settings: ResMut<UserSettings>,
+    mut launch_window: ResMut<LaunchWindow>,
+) {
+    if actions.just_pressed(Action::Fullscreen) {
+        // flips what is shown, which --windowed may differ from
+        settings.fullscreen = !launch_window.fullscreen(&settings);
+        launch_window.windowed = false;
+    }
 }
 
 fn list_scene(
     asset_server: Res<AssetServer>,
     mut commands: Commands,
-    scene_query: Query<Entity, With<SceneMenuList>>,
+    scene_query: Query<Entity, Or<(With<SceneMenuList>, With<ChapterList>)>>,
     mut scene_msg: MessageReader<SceneMsg>,
+    mut filter: ResMut<SceneFilter>,
+    cg: Res<CgGallery>,
     view_res: Res<ViewRes>,
 ) {
     if let Some(event) = scene_msg.read().last() {
         scene_query.iter().for_each(|entity| {
             commands.entity(entity).despawn()
         });
+        filter.index = None;
         commands.spawn((
             Visibility::Visible,
             SceneMenuList,
             ZIndex(Z_UI),
             Node {
-                width: Val::Percent(11.),
+                width: Val::Percent(35.),
                 height: Val::Percent(96.),
-                left: Val::Percent(88.),
+                left: Val::Percent(LIST_LEFT * 100.),
                 top: Val::Percent(1.),
                 align_items: AlignItems::End,
                 flex_direction: FlexDirection::Column,
@@ -603,40 +779,57 @@ fn list_scene(
                 TextColor(HEADTEXT),
                 TextLayout::justify(Justify::Right),
             ));
+            // text is filled in by apply_scene_filter
+            parent.spawn((
+                Button,
+                SearchBox,
+                Node {
+                    width: Val::Percent(100.),
+                    padding: UiRect::all(px(6)),
+                    margin: UiRect::vertical(px(6)),
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(0., 0., 0., 0.4)),
+            )).with_child((
+                SearchText,
+                Text::new(""),
+                TextFont {
+                    font: asset_server.load(FONT).into(),
+                    font_size: FontSize::Px(28.),
+                    ..default()
+                },
+                TextColor(SELECTTEXT),
+            ));
             parent.spawn(Node {
+                width: Val::Percent(100.),
                 display: Display::Grid,
                 grid_template_columns: vec![RepeatedGridTrack::flex(1, 1.), RepeatedGridTrack::auto(1)],
                 grid_template_rows: vec![RepeatedGridTrack::flex(1, 1.), RepeatedGridTrack::auto(1)],
                 ..default()
             }).with_children(|parent| {
                 let scrollable = parent.spawn((
+                    SceneMenuScroll,
                     Node {
-                        align_items: AlignItems::End,
-                        flex_direction: FlexDirection::Column,
+                        display: Display::Grid,
+                        grid_template_columns: RepeatedGridTrack::flex(3, 1.),
+                        align_content: AlignContent::Start,
+                        row_gap: px(12),
+                        column_gap: px(12),
                         overflow: Overflow::scroll_y(),
                         ..default()
                     },
                 )).with_children(|parent| {
-                    let (items, scene_filter):
-                    (&BTreeMap<_, _>, fn(&&String) -> bool) = match event.0 {
-                        ListMode::Memory => (&view_res.events, |_| true),
-                        ListMode::Gallery => (&view_res.spines, |x| x.starts_with("r18")),
-                        ListMode::Motion => (&view_res.spines, |x| !x.starts_with("r18")),
+                    let names: Vec<&String> = match event.0 {
+                        ListMode::Memory => view_res.events.keys().collect(),
+                        ListMode::Gallery => view_res.spines.keys().filter(|x| x.starts_with("r18")).collect(),
+                        ListMode::Motion => view_res.spines.keys().filter(|x| !x.starts_with("r18")).collect(),
+                        ListMode::Cg => {
+                            spawn_cg_cells(parent, &asset_server, &view_res, &cg);
+                            vec!()
+                        }
                     };
-                    for bundle_name in items.keys().filter(scene_filter) {
-                        parent.spawn((
-                            Button,
-                            Text::new(bundle_name),
-                            SceneMenu,
-                            TextFont {
-                                font: asset_server.load(FONT).into(),
-                                font_size: FontSize::Px(36.),
-                                ..default()
-                            },
-                            TextColor(LISTTEXT),
-                            BackgroundColor(Color::NONE),
-                            TextLayout::justify(Justify::Right),
-                        ));
+                    for bundle_name in names {
+                        spawn_scene_cell(parent, &asset_server, bundle_name, bundle_name);
                     }
                 }).id();
                 parent.spawn((
@@ -666,158 +859,228 @@ fn list_scene(
     }
 }
 
+// a SceneMenu button opening `name`, captioned with `label`
+pub(crate) fn spawn_scene_cell(
+    parent: &mut ChildSpawnerCommands,
+    asset_server: &AssetServer,
+    name: &str,
+    label: &str,
+) {
+    parent.spawn((
+        Button,
+        SceneMenu(name.to_string()),
+        Node {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            padding: UiRect::all(px(6)),
+            ..default()
+        },
+        BackgroundColor(Color::NONE),
+    )).with_children(|parent| {
+        // thumbnail is loaded by request_thumbnails
+        parent.spawn((
+            SceneThumb {
+                name: name.to_string(),
+                requested: false,
+            },
+            Node {
+                width: Val::Percent(100.),
+                aspect_ratio: Some(1.),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0., 0., 0., 0.4)),
+        ));
+        parent.spawn((
+            Text::new(label),
+            TextFont {
+                font: asset_server.load(FONT).into(),
+                font_size: FontSize::Px(24.),
+                ..default()
+            },
+            TextColor(LISTTEXT),
+            TextLayout::justify(Justify::Center),
+        ));
+    });
+}
+
 fn choose_scene(
     asset_server: Res<AssetServer>,
     mut commands: Commands,
     mut interaction_query: Query<(
         &Interaction,
-        &Text,
-        &mut TextColor,
         &mut BackgroundColor,
         &SceneMenu,
     ), (Changed<Interaction>, With<Button>),>,
-    spine_query: Query<Entity, With<Spine>>,
-    mut skeletons: ResMut<Assets<SkeletonData>>,
+    spine_query: Query<Entity, (HasSpine, Without<OffscreenSpine>)>,
+    mut skeletons: SkeletonAssets,
     mut vn_ui_msg: MessageWriter<VNToogleMsg>,
+    cg: Res<CgGallery>,
+    compare: Res<CompareMode>,
     mut view_res: ResMut<ViewRes>,
 ) {
-    interaction_query.iter_mut().for_each(|(interaction, text, mut color, mut bg_color, _)| {
+    interaction_query.iter_mut().for_each(|(interaction, mut bg_color, menu)| {
         match *interaction {
             Interaction::Pressed => {
-                let bundle_name = &text.to_string();
-                if view_res.mode == ListMode::Memory {
-                    if let Some(file) = view_res.events.get(bundle_name) && let Ok(content) =
-                            read_to_string(format!("assets/{}/{}.{}", file.path, file.name, file.ext)) {
-                        let book = utage4::parse_book(content);
-                        view_res.avg = true;
-                        view_res.avg_nodes = book;
-                        view_res.avg_offset = 0;
-                        view_res.fast = false;
-                        view_res.wait_timer = None;
-                        view_res.effect_wait = false;
-                        view_res.params = HashMap::new();
-                        view_res.avg_labels = view_res.avg_nodes.iter().enumerate()
-                            .filter(|(_, n)| n.command.as_deref().is_some_and(|c| c.starts_with('*')))
-                            .map(|(i, n)| (n.command.clone().unwrap(), i))
-                            .collect();
-                        view_res.selection = None;
-                        vn_ui_msg.write(VNToogleMsg(true));
-                    }
-                } else if let Some(file) = view_res.spines.get(bundle_name) {
-                    let skeleton = if file.ext == "skel" {
-                        SkeletonData::new_from_binary(
-                            asset_server.load(format!("{}/{}.{}", file.path, file.name, file.ext)),
-                            asset_server.load(format!("{}/{}.atlas", file.path, file.name)),
-                        )
-                    } else {
-                        SkeletonData::new_from_json(
-                            asset_server.load(format!("{}/{}.{}", file.path, file.name, file.ext)),
-                            asset_server.load(format!("{}/{}.atlas", file.path, file.name)),
-                        )
-                    };
-                    let skeleton_handle = skeletons.add(skeleton);
-                    spine_query.iter().for_each(|entity| {
-                        commands.entity(entity).despawn()
-                    });
-                    commands.spawn((
-                        SkeletonDataHandle(skeleton_handle.clone()),
-                        Transform::from_xyz(0., 0., Z_CG as f32).with_scale(Vec3::ONE * 0.5),
-                    ));
-                }
+                open_scene(&menu.0, &asset_server, &mut commands, &spine_query, &mut skeletons, &mut vn_ui_msg, &cg, &compare, &mut view_res);
             }
             Interaction::Hovered => {
-                *color = SELECTTEXT.into();
                 *bg_color = HOVERBG.into();
             }
             _ => {
-                *color = LISTTEXT.into();
                 *bg_color = Color::NONE.into();
             }
         }
     });
 }
 
-fn spine_spawn(
-    asset_server: Res<AssetServer>,
-    mut commands: Commands,
-    mut spine_query: Query<&mut Spine, Without<VNSpine>>,
-    mut vn_spine_query: Query<(&mut Spine, &VNSpine)>,
-    anime_query: Query<Entity, With<AnimeMenuList>>,
-    mut spine_visibility: Query<&mut Visibility, With<Spine>>,
-    mut spine_ready_msg: MessageReader<SpineReadyMsg>,
-    view_res: Res<ViewRes>,
+pub(crate) fn open_scene(
+    bundle_name: &str,
+    asset_server: &Res<AssetServer>,
+    commands: &mut Commands,
+    spine_query: &Query<Entity, (HasSpine, Without<OffscreenSpine>)>,
+    skeletons: &mut SkeletonAssets,
+    vn_ui_msg: &mut MessageWriter<VNToogleMsg>,
+    cg: &CgGallery,
+    compare: &CompareMode,
+    view_res: &mut ResMut<ViewRes>,
 ) {
-    if view_res.mode != ListMode::Memory {
-        for msg in spine_ready_msg.read() {
-            anime_query.iter().for_each(|entity| {
+    if view_res.mode == ListMode::Cg {
+        open_cg(bundle_name, asset_server, commands, spine_query, view_res, cg);
+    } else if view_res.mode == ListMode::Memory {
+        if let Some(book) = load_book(bundle_name, view_res) {
+            let chapters = book_chapters(&book, &view_res.avg_regex);
+            // books with labels can be started from any of them
+            if chapters.is_empty() {
+                start_book(book, 0, vn_ui_msg, view_res);
+            } else {
+                commands.write_message(ChapterMsg {
+                    bundle: bundle_name.to_string(),
+                    chapters,
+                });
+            }
+        }
+    } else if let Some(file) = view_res.spines.get(bundle_name) {
+        // compare mode keeps the others and replaces the oldest once full
+        let full = compare.order.len() >= MAX_COMPARE;
+        spine_query.iter().for_each(|entity| {
+            if !compare.enabled || (full && compare.order.first() == Some(&entity)) {
                 commands.entity(entity).despawn()
-            });
-            let mut animation_list = vec!();
-            if let Ok(mut spine) = spine_query.get_mut(msg.entity) {
-                let Spine(SkeletonController {
-                    animation_state,
-                    ..
-                }) = spine.as_mut();
-                for i in animation_state.data().skeleton_data().animations() {
-                    animation_list.push(i.name().to_string());
-                }
+            } else {
+                commands.entity(entity).remove::<ActiveViewer>();
             }
+        });
+        let mut viewer = commands.spawn((
+            ViewerBundle(bundle_name.to_string()),
+            ActiveViewer,
+            Transform::from_xyz(0., 0., Z_CG as f32).with_scale(Vec3::ONE * 0.5),
+        ));
+        skeletons.insert(&mut viewer, asset_server, file);
+    }
+}
 
-            commands.spawn((
-                Visibility::Visible,
-                AnimeMenuList,
-                ZIndex(Z_UI),
-                Node {
-                    width: Val::Percent(11.),
-                    height: Val::Percent(66.),
-                    left: Val::Percent(1.),
-                    top: Val::Percent(1.),
-                    flex_direction: FlexDirection::Column,
-                    ..default()
-                },
-            )).with_children(|parent| {
-                parent.spawn((
-                    Button,
-                    Text::new("Select Action"),
-                    TextFont {
-                        font: asset_server.load(FONT).into(),
-                        font_size: FontSize::Px(42.),
-                        ..default()
-                    },
-                    TextColor(HEADTEXT),
-                    BackgroundColor(Color::NONE),
-                ));
-                for animation in animation_list {
-                    parent.spawn((
-                        Button,
-                        Text::new(animation),
-                        AnimeMenu,
-                        TextFont {
-                            font: asset_server.load(FONT).into(),
-                            font_size: FontSize::Px(35.),
-                            ..default()
-                        },
-                        TextColor(LISTTEXT),
-                        BackgroundColor(Color::NONE),
-                    ));
-                }
-            });
-        }
-    } else if view_res.avg {
-        for msg in spine_ready_msg.read() {
-            if let Ok((mut spine, s)) = vn_spine_query.get_mut(msg.entity)
+// viewer skeletons are set up by viewer_spine_ready
+fn spine_spawn(
+    mut vn_spine_query: Query<(AnySpine, &VNSpine)>,
+    mut spine_visibility: Query<&mut Visibility, HasSpine>,
+    mut skeleton_ready_msg: MessageReader<SkeletonReadyMsg>,
+    view_res: Res<ViewRes>,
+) {
+    if view_res.mode == ListMode::Memory && view_res.avg {
+        for msg in skeleton_ready_msg.read() {
+            if let Ok((spine, s)) = vn_spine_query.get_mut(msg.entity)
                 && let Ok(mut visibility) = spine_visibility.get_mut(msg.entity) {
                 if &s.1 == "<Off>" {
                     *visibility = Visibility::Hidden;
                 } else {
                     *visibility = Visibility::Visible;
-                    let _ = spine.animation_state.set_animation_by_name(0, &s.1, true);
+                    with_spine!(spine, |spine| {
+                        let _ = spine.animation_state.set_animation_by_name(0, &s.1, true);
+                    });
                 }
             }
         }
     }
 }
 
+// timescale and framing of a new viewer skeleton of either runtime, panels for the active one
+fn viewer_spine_ready(
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+    mut spines: ViewerSpines,
+    active_query: Query<(), With<ActiveViewer>>,
+    anime_query: Query<Entity, With<AnimeMenuList>>,
+    mut skeleton_ready_msg: MessageReader<SkeletonReadyMsg>,
+    control: Res<AnimeControl>,
+    mut skin_selection: ResMut<SkinSelection>,
+) {
+    for msg in skeleton_ready_msg.read() {
+        let Some(bounds) = spines.bounds(msg.entity) else { continue };
+        spines.set_timescale(msg.entity, if control.paused { 0. } else { control.timescale });
+        // placed by layout_viewer
+        commands.entity(msg.entity).insert(SkeletonBounds(bounds));
+        if !active_query.contains(msg.entity) {
+            continue;
+        }
+        anime_query.iter().for_each(|entity| {
+            commands.entity(entity).despawn()
+        });
+        spawn_viewer_panels(&mut commands, &asset_server, &mut spines, msg.entity, &mut skin_selection);
+    }
+}
+
+// animation list, playback control and skin panel of the active viewer spine
+pub(crate) fn spawn_viewer_panels(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    spines: &mut ViewerSpines,
+    entity: Entity,
+    skin_selection: &mut SkinSelection,
+) {
+    let animation_list = spines.animations(entity);
+    spawn_anime_control(commands, asset_server);
+    spawn_skin_panel(commands, asset_server, spines, entity, skin_selection);
+
+    commands.spawn((
+        Visibility::Visible,
+        AnimeMenuList,
+        ZIndex(Z_UI),
+        Node {
+            width: Val::Percent(11.),
+            height: Val::Percent(66.),
+            left: Val::Percent(1.),
+            top: Val::Percent(1.),
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+    )).with_children(|parent| {
+        parent.spawn((
+            Button,
+            Text::new("Select Action"),
+            TextFont {
+                font: asset_server.load(FONT).into(),
+                font_size: FontSize::Px(42.),
+                ..default()
+            },
+            TextColor(HEADTEXT),
+            BackgroundColor(Color::NONE),
+        ));
+        for animation in animation_list {
+            parent.spawn((
+                Button,
+                Text::new(animation),
+                AnimeMenu,
+                TextFont {
+                    font: asset_server.load(FONT).into(),
+                    font_size: FontSize::Px(35.),
+                    ..default()
+                },
+                TextColor(LISTTEXT),
+                BackgroundColor(Color::NONE),
+            ));
+        }
+    });
+}
+
 fn choose_animation(
     mut interaction_query: Query<(
         &Interaction,
@@ -826,13 +1089,15 @@ fn choose_animation(
         &mut BackgroundColor,
         &AnimeMenu,
     ), (Changed<Interaction>, With<Button>),>,
-    mut spine_query: Query<&mut Spine, Without<VNSpine>>,
+    mut spines: ViewerSpines,
+    active_query: Query<Entity, With<ActiveViewer>>,
+    control: Res<AnimeControl>,
 ) {
     interaction_query.iter_mut().for_each(|(interaction, text, mut color, mut bg_color, _)| {
         match *interaction {
             Interaction::Pressed => {
-                spine_query.iter_mut().for_each(|mut spine| {
-                    let _ = spine.animation_state.set_animation_by_name(0, text, true);
+                active_query.iter().for_each(|entity| {
+                    spines.set_animation(entity, control.track, text, control.looping, control.mix);
                 })
             }
             Interaction::Hovered => {
@@ -863,6 +1128,7 @@ fn choose_mode(
                 let mode = match text.as_str() {
                     "Motion" => ListMode::Motion,
                     "Memory" => ListMode::Memory,
+                    "CG" => ListMode::Cg,
                     _ => ListMode::Gallery,
                 };
                 view_res.mode = mode.clone();
@@ -903,7 +1169,7 @@ fn spawn_selection_ui(
         },
     )).with_children(|parent| {
         for (i, t) in texts.iter().enumerate() {
-            let mut bg = SELECTBG.to_srgba();
+            let mut bg = srgb_u8(profile().palette.select_bg).to_srgba();
             if i != 0 {
                 bg.alpha = 0.85;
             }
@@ -917,7 +1183,7 @@ fn spawn_selection_ui(
                     align_items: AlignItems::Center,
                     ..default()
                 },
-                Outline::new(px(4.), Val::ZERO, SELECTBORDER),
+                Outline::new(px(4.), Val::ZERO, srgb_u8(profile().palette.select_border)),
                 BackgroundColor(bg.into()),
             )).with_children(|parent| {
                 parent.spawn((
@@ -927,7 +1193,7 @@ fn spawn_selection_ui(
                         font_size: FontSize::Px(54.),
                         ..default()
                     },
-                    TextColor(VNTEXT),
+                    TextColor(srgb_u8(profile().palette.vn_text)),
                     TextLayout::justify(Justify::Center),
                 ));
             });
@@ -940,7 +1206,7 @@ fn selection_highlight(
     selection: &SelectionState,
 ) {
     selection_query.iter_mut().for_each(|(_, item, mut bg)| {
-        let mut c = SELECTBG.to_srgba();
+        let mut c = srgb_u8(profile().palette.select_bg).to_srgba();
         if item.0 != selection.index {
             c.alpha = 0.85;
         }
@@ -954,16 +1220,19 @@ fn input_handler(
     mut vn_ui_msg: MessageWriter<VNToogleMsg>,
     mut vn_msg: MessageWriter<VNMsg>,
     vn_text: Single<&VNText>,
-    button: Res<ButtonInput<MouseButton>>,
-    key: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     mut selection_query: Query<(&Interaction, &SelectionItem, &mut BackgroundColor)>,
     mut view_res: ResMut<ViewRes>,
 ) {
-    if button.just_pressed(MouseButton::Right) {
+    let exit = actions.just_pressed(Action::Exit);
+    if actions.just_pressed(Action::HideUi) {
         if view_res.avg {
-            vn_ui.iter_mut().for_each(|mut v| {
-                v.toggle_visible_hidden()
-            })
+            // a window hidden by HideMessageWindow stays hidden until ShowMessageWindow
+            if !view_res.window_hidden {
+                vn_ui.iter_mut().for_each(|mut v| {
+                    v.toggle_visible_hidden()
+                })
+            }
         } else {
             viewer_ui.iter_mut().for_each(|mut v| {
                 v.toggle_visible_hidden()
@@ -972,22 +1241,23 @@ fn input_handler(
     }
 
     if view_res.avg {
-        if key.just_released(KeyCode::ControlLeft) || key.just_released(KeyCode::ControlRight) {
+        if actions.just_released(Action::Skip) {
             view_res.fast = false;
         }
-        if key.pressed(KeyCode::ControlLeft) || key.pressed(KeyCode::ControlRight) {
+        if actions.pressed(Action::Skip) && !view_res.skip_forbidden {
             view_res.fast = true;
         }
         if let Some(sel) = &mut view_res.selection {
-            if key.just_pressed(KeyCode::ArrowUp) {
+            if actions.just_pressed(Action::Up) || actions.just_pressed(Action::FocusUp) {
                 sel.index = sel.index.saturating_sub(1);
                 selection_highlight(&mut selection_query, sel);
             }
-            if key.just_pressed(KeyCode::ArrowDown) && sel.index + 1 < sel.texts.len() {
+            if (actions.just_pressed(Action::Down) || actions.just_pressed(Action::FocusDown))
+            && sel.index + 1 < sel.texts.len() {
                 sel.index += 1;
                 selection_highlight(&mut selection_query, sel);
             }
-            if key.just_pressed(KeyCode::Enter) || key.just_pressed(KeyCode::Space) {
+            if actions.just_pressed(Action::Confirm) || actions.just_pressed(Action::Press) {
                 sel.selected = Some(sel.index);
                 vn_msg.write(VNMsg);
             }
@@ -998,7 +1268,7 @@ fn input_handler(
                 sel.selected = Some(idx);
                 vn_msg.write(VNMsg);
             }
-            if key.just_pressed(KeyCode::Escape) {
+            if exit {
                 view_res.avg = false;
                 view_res.wait_timer = None;
                 view_res.effect_wait = false;
@@ -1006,8 +1276,7 @@ fn input_handler(
                 vn_ui_msg.write(VNToogleMsg(false));
             }
         } else {
-            if button.just_pressed(MouseButton::Left)
-            || key.just_pressed(KeyCode::Enter) || key.just_pressed(KeyCode::Space) {
+            if actions.just_pressed(Action::Advance) {
                 if view_res.pending_effects > 0 && !view_res.fast && vn_text.finished() {
                     view_res.wait_timer = Some(Timer::from_seconds(0., TimerMode::Once));
                     view_res.effect_wait = true;
@@ -1015,13 +1284,13 @@ fn input_handler(
                     vn_msg.write(VNMsg);
                 }
             }
-            if key.just_pressed(KeyCode::Escape) {
+            if exit {
                 view_res.avg = false;
                 view_res.wait_timer = None;
                 view_res.effect_wait = false;
                 vn_ui_msg.write(VNToogleMsg(false));
             }
-            if key.just_released(KeyCode::Tab) {
+            if actions.just_pressed(Action::Auto) {
                 view_res.auto = !view_res.auto;
             }
         }
@@ -1034,8 +1303,9 @@ fn toggle_vn(
     mut vn_ui: Query<&mut Visibility, With<VNGui>>,
     mut text: Single<&mut Text2d, With<VNText>>,
     mut vn_text: Single<&mut VNText>,
-    despawn_query: Query<Entity, Or<(With<Spine>, With<AnimeMenuList>)>>,
-    vn_despawn_query: Query<Entity, Or<(With<FadeOverlay>, With<VNTexture>, (With<VNAudio>, Without<AudioFade>), With<WaitEffect>, With<SelectionUI>)>>,
+    camera: Single<(Entity, &mut VNCamera, &mut Transform)>,
+    despawn_query: Query<Entity, (Or<(With<spine38::Spine>, With<spine42::Spine>, With<AnimeMenuList>)>, Without<OffscreenSpine>)>,
+    vn_despawn_query: Query<Entity, Or<(With<FadeOverlay>, With<RuleFade>, With<VNTexture>, (With<VNAudio>, Without<AudioFade>), With<WaitEffect>, With<SelectionUI>)>>,
     mut vn_ui_msg: MessageReader<VNToogleMsg>,
     mut vn_msg: MessageWriter<VNMsg>,
     mut view_res: ResMut<ViewRes>,
@@ -1056,6 +1326,8 @@ fn toggle_vn(
                 commands.entity(entity).despawn()
             });
             view_res.selection = None;
+            let (camera, mut vn_camera, mut transform) = camera.into_inner();
+            reset_camera(&mut commands, camera, &mut vn_camera, &mut transform);
             vn_ui.iter_mut().for_each(|mut v| {
                 *v = Visibility::Hidden
             });
@@ -1069,10 +1341,11 @@ fn toggle_vn(
 fn vn_dialogue(
     mut vn_text: Single<(&mut Text2d, &mut VNText)>,
     fade_query: Query<&FadeOverlay>,
+    rule_query: Query<&RuleFade, Without<ChildOf>>,
     time: Res<Time>,
     view_res: Res<ViewRes>,
 ) {
-    if view_res.avg && fade_query.count() == 0 {
+    if view_res.avg && fade_query.is_empty() && rule_query.is_empty() {
         vn_text.1.timer.tick(time.delta());
         if vn_text.1.timer.just_finished() && vn_text.1.index < vn_text.1.len() {
             vn_text.1.index += 1;
@@ -1127,7 +1400,7 @@ fn fade_sound(
 
 fn check_wait(
     mut vn_msg: MessageWriter<VNMsg>,
-    spine_query: Query<(), With<Spine>>,
+    spine_query: Query<(), HasSpine>,
     time: Res<Time>,
     mut view_res: ResMut<ViewRes>,
 ) {
@@ -1160,7 +1433,8 @@ fn check_wait(
 
 fn check_auto_forward(
     vn_text: Single<&VNText>,
-    audio_query: Query<(Entity, &AudioSink, &VNAudio), Without<AudioFade>>,
+    audio_query: Query<(Entity, Option<&AudioSink>, &VNAudio), Without<AudioFade>>,
+    settings: Res<UserSettings>,
     mut view_res: ResMut<ViewRes>,
 ) {
     if view_res.avg && view_res.auto && !view_res.fast && view_res.wait_timer.is_none()
@@ -1176,17 +1450,20 @@ fn check_auto_forward(
                 view_res.wait_timer = Some(Timer::from_seconds(0., TimerMode::Once));
                 view_res.voice_played = false;
             } else {
-                view_res.wait_timer = Some(Timer::new(AUTOFORWARD, TimerMode::Once));
+                view_res.wait_timer = Some(Timer::new(settings.auto_forward(), TimerMode::Once));
             }
             view_res.forwarded = true;
         }
 }
 
 fn mouse_scroll(
-    mut spine_query: Query<&mut Transform, Or<(With<Spine>, With<VNTexture>)>>,
+    mut spine_query: Query<&mut Transform, (Or<(With<spine38::Spine>, With<spine42::Spine>, With<VNTexture>, With<CgView>)>, Without<OffscreenSpine>)>,
     scrollbar: Single<&Scrollbar>,
     mut scrolled_query: Query<(&mut ScrollPosition, &ComputedNode), Without<Scrollbar>>,
     window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform), With<VNCamera>>,
+    panel_query: Query<&RelativeCursorPosition, Or<(With<SkinPanel>, With<ChapterList>)>>,
+    cg_view: Query<(), With<CgView>>,
     mut scroll: MessageReader<MouseWheel>,
     time: Res<Time>,
 ) {
@@ -1196,7 +1473,8 @@ fn mouse_scroll(
         }
         let delta_secs = time.delta_secs();
         if let Some(pos) = window.cursor_position() {
-            if pos.x > window.width() * 0.88 {
+            // the whole screen zooms an open cg
+            if pos.x > window.width() * LIST_LEFT && cg_view.is_empty() {
                 if let Ok((mut scroll_pos, scroll_content)) = scrolled_query.get_mut(scrollbar.target) {
                     let visible_size = scroll_content.size() * scroll_content.inverse_scale_factor;
                     let content_size = scroll_content.content_size() * scroll_content.inverse_scale_factor;
@@ -1204,9 +1482,15 @@ fn mouse_scroll(
                     scroll_pos.y -= ev.y * 5000. * delta_secs;
                     scroll_pos.y = scroll_pos.y.clamp(0., range);
                 };
-            } else {
+            } else if !panel_query.iter().any(|cursor| cursor.cursor_over) {
+                // zoom around the point under the cursor
+                let (camera, camera_transform) = *camera;
+                let Ok(pivot) = camera.viewport_to_world_2d(camera_transform, pos) else { continue };
+                let factor = (1. + ev.y * 0.2 * delta_secs).max(0.5);
                 spine_query.iter_mut().for_each(|mut spine| {
-                    spine.scale += ev.y * 0.1 * delta_secs
+                    let offset = (spine.translation.truncate() - pivot) * factor;
+                    spine.translation = (pivot + offset).extend(spine.translation.z);
+                    spine.scale *= factor;
                 });
             }
         }
@@ -1214,19 +1498,24 @@ fn mouse_scroll(
 }
 
 fn mouse_object_move(
-    mut object_query: Query<&mut Transform, Or<(With<Spine>, With<VNTexture>)>>,
+    mut object_query: Query<&mut Transform, (Or<(With<spine38::Spine>, With<spine42::Spine>, With<VNTexture>, With<CgView>)>, Without<OffscreenSpine>)>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform), With<VNCamera>>,
     mut motion: MessageReader<MouseMotion>,
-    button: Res<ButtonInput<MouseButton>>,
-    time: Res<Time>,
+    actions: Res<ActionState>,
 ) {
-    if button.pressed(MouseButton::Middle) {
-        let delta_secs = time.delta_secs();
-        for ev in motion.read() {
-            object_query.iter_mut().for_each(|mut obj| {
-                obj.translation.x += ev.delta.x * 6. * delta_secs;
-                obj.translation.y -= ev.delta.y * 6. * delta_secs;
-            })
-        }
+    if actions.pressed(Action::Pan) {
+        let delta: Vec2 = motion.read().map(|ev| ev.delta).sum();
+        let Some(pos) = window.cursor_position() else { return };
+        let (camera, camera_transform) = *camera;
+        // objects follow the cursor in world units
+        let (Ok(from), Ok(to)) = (
+            camera.viewport_to_world_2d(camera_transform, pos - delta),
+            camera.viewport_to_world_2d(camera_transform, pos),
+        ) else { return };
+        object_query.iter_mut().for_each(|mut obj| {
+            obj.translation += (to - from).extend(0.);
+        })
     }
 }
 
@@ -1236,14 +1525,17 @@ fn play_vn(
     mut vn_char: Single<&mut Text2d, With<VNChar>>,
     mut vn_text: Single<(&mut Text2d, &mut VNText), Without<VNChar>>,
     mut vn_ui: Query<&mut Visibility, With<VNGui>>,
-    mut audio_query: Query<(Entity, &AudioSink, &VNAudio), Without<AudioFade>>,
+    mut audio_query: Query<(Entity, Option<&AudioSink>, &VNAudio), Without<AudioFade>>,
     mut tex_query: Query<(Entity, &mut VNTexture, &mut Transform, &mut Sprite), (Without<VNSpine>, Without<VNGui>)>,
-    mut spine_query: Query<(Entity, &mut Spine, &mut VNSpine, &mut Transform), (Without<VNTexture>, Without<VNGui>)>,
-    mut gui_query: Query<(Entity, &VNGui, &mut Transform), (Without<VNSpine>, Without<VNTexture>)>,
-    mut spine_visibility: Query<&mut Visibility, (With<Spine>, Without<VNGui>)>,
+    mut spine_query: Query<(Entity, AnySpine, &mut VNSpine, &mut Transform), (Without<VNTexture>, Without<VNGui>)>,
+    mut gui_query: Query<(Entity, Option<&mut VNCamera>, &mut Transform), (Or<(With<VNGui>, With<VNCamera>)>, Without<VNSpine>, Without<VNTexture>)>,
+    mut spine_visibility: Query<&mut Visibility, (HasSpine, Without<VNGui>)>,
+    effect_query: Query<&ImageEffect>,
     mut vn_msg: MessageReader<VNMsg>,
     mut vn_ui_msg: MessageWriter<VNToogleMsg>,
-    mut skeletons: ResMut<Assets<SkeletonData>>,
+    mut skeletons: SkeletonAssets,
+    // grouped to stay within the system parameter limit
+    (mixer, mut cg, settings, mut audio_paths): (Res<AudioMixer>, ResMut<CgGallery>, Res<UserSettings>, ResMut<AudioPaths>),
     mut view_res: ResMut<ViewRes>,
 ) {
     if vn_msg.read().last().is_some() {
@@ -1258,7 +1550,7 @@ fn play_vn(
                     None => {
                         let (wait, entity) = default_cmd(
                             node, &asset_server, &mut commands, &mut vn_char, &mut vn_text, &mut vn_ui,
-                            &mut audio_query, &mut spine_query, &mut spine_visibility, &mut skeletons, &view_res);
+                            &mut audio_query, &mut spine_query, &mut spine_visibility, &mut skeletons, &mut audio_paths, &mixer, &settings, &view_res);
                         if let Some(entity) = entity {
                             view_res.spine_cache.push(entity);
                         }
@@ -1272,7 +1564,7 @@ fn play_vn(
                         character_off_cmd(node, &mut commands, &mut spine_query, true);
                     }
                     Some(f @ "Bg") | Some(f @ "BgEvent") | Some(f @ "Sprite") => {
-                        img_cmd(f, node, &asset_server, &mut commands, &view_res);
+                        img_cmd(f, node, &asset_server, &mut commands, &mut cg, &view_res);
                     }
                     Some(f @ "BgOff") | Some(f @ "BgEventOff") => {
                         bg_off_cmd(f, &mut commands, &mut tex_query);
@@ -1285,14 +1577,14 @@ fn play_vn(
                     }
                     Some(f @ "Se") | Some(f @ "Bgm") | Some(f @ "Ambience")
                     | Some(f @ "HSe") | Some(f @ "BgVoice") => {
-                        sound_cmd(f, node, &asset_server, &mut commands, &mut audio_query, &view_res);
+                        sound_cmd(f, node, &asset_server, &mut commands, &mut audio_query, &mut audio_paths, &view_res);
                     }
                     Some(f @ "StopSe") | Some(f @ "StopBgm") | Some(f @ "StopAmbience")
                     | Some(f @ "StopHSe") | Some(f @ "StopBgVoice") => {
                         stop_sound_item_cmd(f, node, &mut commands, &mut audio_query, false);
                     }
                     Some("Voice") => {
-                        voice_cmd(node, &asset_server, &mut commands, &mut audio_query);
+                        voice_cmd(node, &asset_server, &mut commands, &mut audio_query, &mut audio_paths, &mixer);
                     }
                     Some("StopVoice") => {
                         stop_voice_cmd(&mut commands, &mut audio_query);
@@ -1315,6 +1607,53 @@ fn play_vn(
                             break;
                         }
                     }
+                    Some(f @ "RuleFadeOut") | Some(f @ "RuleFadeIn") => {
+                        rule_fade_cmd(f, node, &asset_server, &mut commands, &mut spine_query, &mut tex_query, &view_res);
+                        if should_wait(&node.wait_type) {
+                            view_res.wait_timer = Some(Timer::from_seconds(0., TimerMode::Once));
+                            view_res.effect_wait = true;
+                            view_res.avg_offset += 1;
+                            break;
+                        }
+                    }
+                    Some(f @ "ImageEffect") | Some(f @ "ImageEffectOff") => {
+                        image_effect_cmd(f, node, &mut commands, &mut spine_query, &mut tex_query, &effect_query);
+                        // effects without time are applied immediately
+                        f32!(t = node.arg6, 0.);
+                        if should_wait(&node.wait_type) && t > 0. {
+                            view_res.wait_timer = Some(Timer::from_seconds(0., TimerMode::Once));
+                            view_res.effect_wait = true;
+                            view_res.avg_offset += 1;
+                            break;
+                        }
+                    }
+                    Some("SkipOff") => {
+                        view_res.skip_forbidden = true;
+                        view_res.fast = false;
+                    }
+                    Some("SkipOn") => {
+                        view_res.skip_forbidden = false;
+                    }
+                    Some("AutoOn") => {
+                        view_res.auto = true;
+                    }
+                    Some("AutoOff") => {
+                        view_res.auto = false;
+                    }
+                    Some("HideMessageWindow") => {
+                        view_res.window_hidden = true;
+                        vn_ui.iter_mut().for_each(|mut v| {
+                            *v = Visibility::Hidden
+                        });
+                    }
+                    Some("ShowMessageWindow") => {
+                        view_res.window_hidden = false;
+                        if !vn_text.1.text.is_empty() {
+                            vn_ui.iter_mut().for_each(|mut v| {
+                                *v = Visibility::Visible
+                            });
+                        }
+                    }
                     Some("Param") => {
                         if let Some((k, v)) = param_cmd(node) {
                             view_res.params.insert(k, v);
@@ -1329,6 +1668,16 @@ fn play_vn(
                             break;
                         }
                     }
+                    Some("Zoom") | Some("ZoomCamera") => {
+                        zoom_cmd(node, &mut commands, &mut gui_query);
+                        f32!(t = node.arg6, 0.);
+                        if should_wait(&node.wait_type) && t > 0. {
+                            view_res.wait_timer = Some(Timer::from_seconds(0., TimerMode::Once));
+                            view_res.effect_wait = true;
+                            view_res.avg_offset += 1;
+                            break;
+                        }
+                    }
                     Some("Tween") => {
                         if view_res.spine_cache.is_empty() {
                             tween_cmd(node, &mut commands, &mut spine_query, &mut tex_query, &mut gui_query);
@@ -1382,7 +1731,7 @@ fn play_vn(
                                     break;
                                 };
                                 labels.push(arg1);
-                                texts.push(sn.text.clone().unwrap_or_default());
+                                texts.push(sn.text_in(settings.language.as_deref()).cloned().unwrap_or_default());
                                 n += 1;
                             }
                             if labels.is_empty() {
@@ -1412,19 +1761,22 @@ fn play_vn(
             }
         } else {
             vn_text.1.skip_to_end();
-            vn_ui.iter_mut().for_each(|mut v| {
-                *v = Visibility::Visible
-            })
+            if !view_res.window_hidden {
+                vn_ui.iter_mut().for_each(|mut v| {
+                    *v = Visibility::Visible
+                })
+            }
         }
     }
 }
 
-fn normalize(text: &str, view_res: &ResMut<ViewRes>) -> String {
+// intervals are counted in characters shown every `char_time`
+fn normalize(text: &str, char_time: Duration, view_res: &ResMut<ViewRes>) -> String {
     view_res.avg_regex.replace_all(text, |caps: &Captures| {
         if let Some(p) = caps.name("interval") {
             let key = p.as_str();
             return key.parse::<f32>()
-                .map(|num| (num / VNSPEED.as_secs_f32()).ceil())
+                .map(|num| (num / char_time.as_secs_f32()).ceil())
                 .map(|count| {
                     if count > 0.0 {
                         "\u{200c}".repeat(count as usize)
@@ -1454,22 +1806,28 @@ fn default_cmd(
     vn_char: &mut Single<&mut Text2d, With<VNChar>>,
     vn_text: &mut Single<(&mut Text2d, &mut VNText), Without<VNChar>>,
     vn_ui: &mut Query<&mut Visibility, With<VNGui>>,
-    audio_query: &mut Query<(Entity, &AudioSink, &VNAudio), Without<AudioFade>>,
-    spine_query: &mut Query<(Entity, &mut Spine, &mut VNSpine, &mut Transform), (Without<VNTexture>, Without<VNGui>)>,
-    spine_visibility: &mut Query<&mut Visibility, (With<Spine>, Without<VNGui>)>,
-    skeletons: &mut ResMut<Assets<SkeletonData>>,
+    audio_query: &mut Query<(Entity, Option<&AudioSink>, &VNAudio), Without<AudioFade>>,
+    spine_query: &mut Query<(Entity, AnySpine, &mut VNSpine, &mut Transform), (Without<VNTexture>, Without<VNGui>)>,
+    spine_visibility: &mut Query<&mut Visibility, (HasSpine, Without<VNGui>)>,
+    skeletons: &mut SkeletonAssets,
+    audio_paths: &mut AudioPaths,
+    mixer: &Res<AudioMixer>,
+    settings: &UserSettings,
     view_res: &ResMut<ViewRes>,
 ) -> (bool, Option<Entity>) {
     let mut wait = false;
     let mut spine_entity = None;
+    let char_time = vn_text.1.timer.duration();
     // dialogue text
-    if let Some(t) = &node.text {
-        let text = normalize(t, view_res);
+    if let Some(t) = node.text_in(settings.language.as_deref()) {
+        let text = normalize(t, char_time, view_res);
         vn_text.0.0 = "".into();
         vn_text.1.update(&text);
-        vn_ui.iter_mut().for_each(|mut v| {
-            *v = Visibility::Visible
-        });
+        if !view_res.window_hidden {
+            vn_ui.iter_mut().for_each(|mut v| {
+                *v = Visibility::Visible
+            });
+        }
         wait = true;
     } else {
         vn_ui.iter_mut().for_each(|mut v| {
@@ -1478,30 +1836,32 @@ fn default_cmd(
     }
     // play voice
     if let Some(voice) = &node.voice {
-        stop_voice_cmd(commands, audio_query);
-        info!("play voice {}", voice);
-        commands.spawn((
-            VNAudio(AudioType::Voice, "".into()),
-            AudioPlayer::new(
-                asset_server.load(format!("{}{}.m4a", VOICE, voice))
-            ),
-            PlaybackSettings {
-                mode: PlaybackMode::Despawn,
-                volume: Volume::Linear(1.),
-                ..default()
-            },
-        ));
+        // voices are tagged with the speaker's CharacterName for per character volume
+        let speaker = str!(node.arg1);
+        stop_previous_voice(speaker, commands, audio_query, mixer);
+        info!("play voice {} of {}", voice, speaker);
+        if let Some(path) = audio_paths.resolve(&VOICE, voice) {
+            commands.spawn((
+                VNAudio(AudioType::Voice, speaker.into()),
+                AudioPlayer::new(asset_server.load(path)),
+                PlaybackSettings {
+                    mode: PlaybackMode::Despawn,
+                    volume: Volume::Linear(1.),
+                    ..default()
+                },
+            ));
+        }
     }
     // draw character and update dialogue character name
     let char_name = str!(node.arg1);
     if let (Some(character), Some(motion)) = (view_res.vn.character.get(char_name), node.arg2.as_deref()) {
         if let Some(name_text) = character.name_text.as_deref() {
-            vn_char.0 = normalize(name_text, view_res)
+            vn_char.0 = normalize(name_text, char_time, view_res)
         } else {
             vn_char.0 = char_name.into();
         }
         let mut spine_spawned = false;
-        spine_query.iter_mut().for_each(|(e, mut spine, mut s, _)| {
+        spine_query.iter_mut().for_each(|(e, spine, mut s, _)| {
             if s.0 == char_name {
                 spine_spawned = true;
                 s.1 = motion.into();
@@ -1510,7 +1870,9 @@ fn default_cmd(
                         *visibility = Visibility::Hidden;
                     } else {
                         *visibility = Visibility::Visible;
-                        let _ = spine.animation_state.set_animation_by_name(0, motion, true);
+                        with_spine!(spine, |spine| {
+                            let _ = spine.animation_state.set_animation_by_name(0, motion, true);
+                        });
                     }
                 }
             }
@@ -1535,20 +1897,7 @@ fn default_cmd(
                             .find(|(k, _)| k.eq_ignore_ascii_case(&bundle_name))
                             .map(|(_, v)| v)
                     }) {
-                        let skeleton = if file.ext == "skel" {
-                            SkeletonData::new_from_binary(
-                                asset_server.load(format!("{}/{}.{}", file.path, file.name, file.ext)),
-                                asset_server.load(format!("{}/{}.atlas", file.path, file.name)),
-                            )
-                        } else {
-                            SkeletonData::new_from_json(
-                                asset_server.load(format!("{}/{}.{}", file.path, file.name, file.ext)),
-                                asset_server.load(format!("{}/{}.atlas", file.path, file.name)),
-                            )
-                        };
-                        let skeleton_handle = skeletons.add(skeleton);
-                        spine_entity = Some(commands.spawn((
-                            SkeletonDataHandle(skeleton_handle.clone()),
+                        let mut spine = commands.spawn((
                             Transform::from_xyz((x + f32!(off_x, 0.)) * SPINE_SCALE,
                                                 (y + f32!(off_y, 0.)) * SPINE_SCALE * 0.5, z)
                                 .with_scale(Vec3::new(scale_x * SPINE_SCALE, scale_y * SPINE_SCALE, 1.)),
@@ -1559,7 +1908,9 @@ fn default_cmd(
                                     avg: Transform::from_xyz(f32!(off_x, 0.), f32!(off_y, 0.), z).with_scale(Vec3::ONE)
                                 }
                             )
-                        )).id());
+                        ));
+                        skeletons.insert(&mut spine, asset_server, file);
+                        spine_entity = Some(spine.id());
                     }
                 }
             }
@@ -1596,11 +1947,28 @@ fn fade_overlay_cmd(
     }
 }
 
+/// Image folder and scale of a texture file, by its type in the reference sheet or else the script command
+pub(crate) fn texture_folder(texture: &utage4::TextureEntry, f: &str) -> Option<(&'static str, f32)> {
+    let texture_type = match texture.entry_type.as_deref() {
+        Some("Bg") => "Bg",
+        Some("Event") => "BgEvent",
+        Some("Sprite") => "Sprite",
+        _ => f,
+    };
+    match texture_type {
+        "Bg" => Some((BG.as_str(), BG_SCALE)),
+        "BgEvent" => Some((EVENT.as_str(), EVENT_SCALE)),
+        "Sprite" => Some((SPRITE.as_str(), SPRITE_SCALE)),
+        _ => None
+    }
+}
+
 fn img_cmd(
     f: &str,
     node: &utage4::Node,
     asset_server: &Res<AssetServer>,
     commands: &mut Commands,
+    cg: &mut ResMut<CgGallery>,
     view_res: &ResMut<ViewRes>,
 ) {
     /* Texture types in the reference sheet may differ from their usage in scripts.
@@ -1614,20 +1982,11 @@ fn img_cmd(
         _ => return
     };
     if let Some(texture) = view_res.vn.texture.get(label_name) {
+        if cg_category(texture).is_some() {
+            unlock_cg(cg, label_name);
+        }
         let layer = view_res.vn.layer.get(str!(node.arg3));
-        // type for texture file search
-        let texture_type = match texture.entry_type.as_deref() {
-            Some("Bg") => "Bg",
-            Some("Event") => "BgEvent",
-            Some("Sprite") => "Sprite",
-            _ => f,
-        };
-        let (img_path, scale) = match texture_type {
-            "Bg" => (BG, BG_SCALE),
-            "BgEvent" => (EVENT, EVENT_SCALE),
-            "Sprite" => (SPRITE, SPRITE_SCALE),
-            _ => return
-        };
+        let Some((img_path, scale)) = texture_folder(texture, f) else { return };
         // command arg + (texture > layer > preset)
         f32!(x = (node.arg4.as_deref().or(texture.x.as_deref()).or_else(|| layer.and_then(|l| l.x.as_deref()))), 0.);
         f32!(y = (node.arg5.as_deref().or(texture.y.as_deref()).or_else(|| layer.and_then(|l| l.y.as_deref()))), 0.);
@@ -1655,7 +2014,7 @@ fn img_cmd(
 fn character_off_cmd(
     node: &utage4::Node,
     commands: &mut Commands,
-    spine_query: &mut Query<(Entity, &mut Spine, &mut VNSpine, &mut Transform), (Without<VNTexture>, Without<VNGui>)>,
+    spine_query: &mut Query<(Entity, AnySpine, &mut VNSpine, &mut Transform), (Without<VNTexture>, Without<VNGui>)>,
     match_label: bool,
 ) {
     spine_query.iter_mut()
@@ -1717,7 +2076,7 @@ fn layer_off_cmd(
     node: &utage4::Node,
     commands: &mut Commands,
     tex_query: &mut Query<(Entity, &mut VNTexture, &mut Transform, &mut Sprite), (Without<VNSpine>, Without<VNGui>)>,
-    spine_query: &mut Query<(Entity, &mut Spine, &mut VNSpine, &mut Transform), (Without<VNTexture>, Without<VNGui>)>,
+    spine_query: &mut Query<(Entity, AnySpine, &mut VNSpine, &mut Transform), (Without<VNTexture>, Without<VNGui>)>,
 ) {
     character_off_cmd(node, commands, spine_query, false);
     tex_query.iter_mut()
@@ -1735,12 +2094,13 @@ fn sound_cmd(
     node: &utage4::Node,
     asset_server: &Res<AssetServer>,
     commands: &mut Commands,
-    audio_query: &mut Query<(Entity, &AudioSink, &VNAudio), Without<AudioFade>>,
+    audio_query: &mut Query<(Entity, Option<&AudioSink>, &VNAudio), Without<AudioFade>>,
+    audio_paths: &mut AudioPaths,
     view_res: &ResMut<ViewRes>,
 ) {
     let sound = match f {
         "BgVoice" => Some(&utage4::SoundEntry{
-            file_name: node.voice.clone().map(|v| v.to_lowercase() + ".m4a"),
+            file_name: node.voice.clone(),
             ..default()
         }),
         _ => view_res.vn.sound.get(str!(node.arg1)),
@@ -1749,11 +2109,11 @@ fn sound_cmd(
         f32!(volume = (node.arg3.as_deref()).or(sound.volume.as_deref()), 1.);
         let file = str!(sound.file_name);
         let (audio_path, audio_type, mut loop_type) = match f {
-            "Se" => (SE, AudioType::Se, PlaybackMode::Despawn),
-            "Bgm" => (BGM, AudioType::Bgm, PlaybackMode::Loop),
-            "Ambience" => (AMBIENCE, AudioType::Ambience, PlaybackMode::Loop),
-            "HSe" => (SE, AudioType::Se, PlaybackMode::Loop),
-            "BgVoice" => (VOICE, AudioType::Se, PlaybackMode::Loop),
+            "Se" => (SE.as_str(), AudioType::Se, PlaybackMode::Despawn),
+            "Bgm" => (BGM.as_str(), AudioType::Bgm, PlaybackMode::Loop),
+            "Ambience" => (profile().ambience.as_str(), AudioType::Ambience, PlaybackMode::Loop),
+            "HSe" => (SE.as_str(), AudioType::Se, PlaybackMode::Loop),
+            "BgVoice" => (VOICE.as_str(), AudioType::Se, PlaybackMode::Loop),
             _ => return
         };
         match node.arg2.as_deref() {
@@ -1768,26 +2128,41 @@ fn sound_cmd(
                 .filter(|x| x.2.0 == audio_type)
                 .for_each(|(entity, sink, vn)| {
                     info!("fade out {}", vn.1);
-                    commands.entity(entity).insert(AudioFade(
-                        Timer::from_seconds(fade_time, TimerMode::Once),
-                        sink.volume()
-                    ));
+                    fade_out_audio(commands, entity, sink, fade_time);
                 }
             )
         }
+        let Some(path) = audio_paths.resolve(audio_path, file) else {
+            return
+        };
         info!("play sound {:?}", sound);
-        commands.spawn((
-            VNAudio(audio_type, str!(node.arg1).into()),
-            AudioPlayer::new(
-                // replace file extension to m4a
-                asset_server.load(format!("{}{}.m4a", audio_path, &file[.. file.len() - 4].to_lowercase()))
-            ),
-            PlaybackSettings {
-                mode: loop_type,
-                volume: Volume::Linear(volume),
-                ..default()
-            },
-        ));
+        let source = asset_server.load(path);
+        let intro = str!(sound.intro_time).parse::<f32>().ok().filter(|&t| t > 0.);
+        if let Some(intro) = intro && matches!(loop_type, PlaybackMode::Loop) {
+            // the decoder loops from the intro time by itself
+            commands.spawn((
+                VNAudio(audio_type, str!(node.arg1).into()),
+                IntroLoop {
+                    source,
+                    intro: Duration::from_secs_f32(intro),
+                },
+                PlaybackSettings {
+                    mode: PlaybackMode::Once,
+                    volume: Volume::Linear(volume),
+                    ..default()
+                },
+            ));
+        } else {
+            commands.spawn((
+                VNAudio(audio_type, str!(node.arg1).into()),
+                AudioPlayer::new(source),
+                PlaybackSettings {
+                    mode: loop_type,
+                    volume: Volume::Linear(volume),
+                    ..default()
+                },
+            ));
+        }
     }
 }
 
@@ -1795,7 +2170,7 @@ fn stop_sound_item_cmd(
     f: &str,
     node: &utage4::Node,
     commands: &mut Commands,
-    audio_query: &mut Query<(Entity, &AudioSink, &VNAudio), Without<AudioFade>>,
+    audio_query: &mut Query<(Entity, Option<&AudioSink>, &VNAudio), Without<AudioFade>>,
     ignore_label: bool,
 ) {
     f32!(fade_time = node.arg6, 0.2);
@@ -1813,18 +2188,28 @@ fn stop_sound_item_cmd(
             type_match && label_match && x.2.0 != AudioType::Voice
         }).for_each(|(entity, sink, vn)| {
             info!("fade out {}", vn.1);
+            fade_out_audio(commands, entity, sink, fade_time);
+        }
+    )
+}
+
+// audio still loading has no sink to fade, it is dropped before it starts
+fn fade_out_audio(commands: &mut Commands, entity: Entity, sink: Option<&AudioSink>, fade_time: f32) {
+    match sink {
+        Some(sink) => {
             commands.entity(entity).insert(AudioFade(
                 Timer::from_seconds(fade_time, TimerMode::Once),
                 sink.volume()
             ));
         }
-    )
+        None => commands.entity(entity).despawn(),
+    }
 }
 
 fn stop_sound_cmd(
     node: &utage4::Node,
     commands: &mut Commands,
-    audio_query: &mut Query<(Entity, &AudioSink, &VNAudio), Without<AudioFade>>,
+    audio_query: &mut Query<(Entity, Option<&AudioSink>, &VNAudio), Without<AudioFade>>,
 ) {
     let parts = match node.arg1.as_deref() {
         None => vec!["Bgm", "Ambience"],
@@ -1855,7 +2240,9 @@ fn voice_cmd(
     node: &utage4::Node,
     asset_server: &Res<AssetServer>,
     commands: &mut Commands,
-    audio_query: &mut Query<(Entity, &AudioSink, &VNAudio), Without<AudioFade>>,
+    audio_query: &mut Query<(Entity, Option<&AudioSink>, &VNAudio), Without<AudioFade>>,
+    audio_paths: &mut AudioPaths,
+    mixer: &Res<AudioMixer>,
 ) {
     if let Some(voice) = &node.voice {
         f32!(volume = node.arg3, 1.);
@@ -1863,13 +2250,15 @@ fn voice_cmd(
             Some("TRUE") => PlaybackMode::Loop,
             _ => PlaybackMode::Despawn,
         };
-        stop_voice_cmd(commands, audio_query);
-        info!("play voice {}", voice);
+        let speaker = str!(node.arg1);
+        stop_previous_voice(speaker, commands, audio_query, mixer);
+        let Some(path) = audio_paths.resolve(&VOICE, voice) else {
+            return
+        };
+        info!("play voice {} of {}", voice, speaker);
         commands.spawn((
-            VNAudio(AudioType::Voice, "".into()),
-            AudioPlayer::new(
-                asset_server.load(format!("{}{}.m4a", VOICE, voice.to_lowercase()))
-            ),
+            VNAudio(AudioType::Voice, speaker.into()),
+            AudioPlayer::new(asset_server.load(path)),
             PlaybackSettings {
                 mode: loop_type,
                 volume: Volume::Linear(volume),
@@ -1881,7 +2270,7 @@ fn voice_cmd(
 
 fn stop_voice_cmd(
     commands: &mut Commands,
-    audio_query: &mut Query<(Entity, &AudioSink, &VNAudio), Without<AudioFade>>,
+    audio_query: &mut Query<(Entity, Option<&AudioSink>, &VNAudio), Without<AudioFade>>,
 ) {
     audio_query.iter_mut().filter(|x| matches!(x.2.0, AudioType::Voice)).for_each(|(entity, _, _)| {
         info!("stop unfinished voice");
@@ -1889,6 +2278,25 @@ fn stop_voice_cmd(
     })
 }
 
+// with keep_voice only the speaker's own unfinished line is cut off
+fn stop_previous_voice(
+    speaker: &str,
+    commands: &mut Commands,
+    audio_query: &mut Query<(Entity, Option<&AudioSink>, &VNAudio), Without<AudioFade>>,
+    mixer: &Res<AudioMixer>,
+) {
+    if !mixer.keep_voice {
+        stop_voice_cmd(commands, audio_query);
+        return
+    }
+    audio_query.iter_mut()
+        .filter(|x| matches!(x.2.0, AudioType::Voice) && x.2.1 == speaker)
+        .for_each(|(entity, _, _)| {
+            info!("stop unfinished voice of {}", speaker);
+            commands.entity(entity).despawn()
+        })
+}
+
 fn param_cmd(node: &utage4::Node) -> Option<(String, String)> {
     let pattern = str!(node.arg1).replace("\\\"", "");
     if let Some((k, v)) = pattern.split_once('=')
@@ -1901,9 +2309,9 @@ fn param_cmd(node: &utage4::Node) -> Option<(String, String)> {
 fn shake_cmd(
     node: &utage4::Node,
     commands: &mut Commands,
-    spine_query: &mut Query<(Entity, &mut Spine, &mut VNSpine, &mut Transform), (Without<VNTexture>, Without<VNGui>)>,
+    spine_query: &mut Query<(Entity, AnySpine, &mut VNSpine, &mut Transform), (Without<VNTexture>, Without<VNGui>)>,
     tex_query: &mut Query<(Entity, &mut VNTexture, &mut Transform, &mut Sprite), (Without<VNSpine>, Without<VNGui>)>,
-    gui_query: &mut Query<(Entity, &VNGui, &mut Transform), (Without<VNSpine>, Without<VNTexture>)>,
+    gui_query: &mut Query<(Entity, Option<&mut VNCamera>, &mut Transform), (Or<(With<VNGui>, With<VNCamera>)>, Without<VNSpine>, Without<VNTexture>)>,
 ) {
     let mut args = Vec::new();
     if let Some(arg3) = node.arg3.as_deref() {
@@ -1932,13 +2340,13 @@ fn shake_cmd(
 fn tween_cmd(
     node: &utage4::Node,
     commands: &mut Commands,
-    spine_query: &mut Query<(Entity, &mut Spine, &mut VNSpine, &mut Transform), (Without<VNTexture>, Without<VNGui>)>,
+    spine_query: &mut Query<(Entity, AnySpine, &mut VNSpine, &mut Transform), (Without<VNTexture>, Without<VNGui>)>,
     tex_query: &mut Query<(Entity, &mut VNTexture, &mut Transform, &mut Sprite), (Without<VNSpine>, Without<VNGui>)>,
-    gui_query: &mut Query<(Entity, &VNGui, &mut Transform), (Without<VNSpine>, Without<VNTexture>)>,
+    gui_query: &mut Query<(Entity, Option<&mut VNCamera>, &mut Transform), (Or<(With<VNGui>, With<VNCamera>)>, Without<VNSpine>, Without<VNTexture>)>,
 ) {
     // MessageWindow = VNGui
     // Graphics = VNSpine + VNTexture
-    // Camera = VNGui + VNSpine + VNTexture
+    // Camera = VNCamera
     if let Some(t) = Tween::new(node) {
         let should_wait = should_wait(&node.wait_type);
         macro_rules! absxyz {
@@ -1989,7 +2397,7 @@ fn tween_cmd(
         }
 
         spine_query.iter_mut()
-            .filter(|x| ["Graphics", "Camera"].contains(&t.target.as_str()) || t.target == x.2.0)
+            .filter(|x| t.target == "Graphics" || t.target == x.2.0)
             .for_each(|mut x| {
                 match t.tween_type {
                     TweenType::MoveTo => {
@@ -2042,12 +2450,14 @@ fn tween_cmd(
                         tween!(TransformScaleLens, Transform, x.3.scale, end, x.0);
                     },
                     TweenType::ColorTo | TweenType::ColorFrom => {
-                        let start = Color::from(Srgba::from_f32_array(x.1.skeleton.get_color()));
+                        let Some(color) = with_spine!(&mut x.1, |spine| spine.skeleton.get_color()) else { return };
+                        let start = Color::from(Srgba::from_f32_array(color));
                         let end = calc_color(&t);
-                        if t.tween_type == TweenType::ColorTo {
-                            tween!(SpineColorLens, Spine, start, end, x.0);
+                        let (start, end) = if t.tween_type == TweenType::ColorTo { (start, end) } else { (end, start) };
+                        if x.1.v38.is_some() {
+                            tween!(SpineColorLens38, spine38::Spine, start, end, x.0);
                         } else {
-                            tween!(SpineColorLens, Spine, end, start, x.0);
+                            tween!(SpineColorLens42, spine42::Spine, start, end, x.0);
                         }
                     },
                     TweenType::PunchPosition | TweenType::ShakePosition
@@ -2066,7 +2476,7 @@ fn tween_cmd(
         );
 
         tex_query.iter_mut()
-            .filter(|x| ["Graphics", "Camera"].contains(&t.target.as_str()) || t.target == x.1.1)
+            .filter(|x| t.target == "Graphics" || t.target == x.1.1)
             .for_each(|mut x| {
                 match t.tween_type {
                     TweenType::MoveTo => {
@@ -2137,7 +2547,7 @@ fn tween_cmd(
         );
 
         gui_query.iter_mut()
-            .filter(|_| ["MessageWindow", "Camera"].contains(&t.target.as_str()))
+            .filter(|x| x.1.is_none() && t.target == "MessageWindow")
             .for_each(|x| {
                 match t.tween_type {
                     TweenType::MoveBy => {
@@ -2169,6 +2579,51 @@ fn tween_cmd(
                 };
             }
         );
+
+        gui_query.iter_mut()
+            .filter(|x| x.1.is_some() && t.target == "Camera")
+            .for_each(|x| {
+                // camera position in background pixels
+                let pos = x.2.translation / BG_SCALE;
+                match t.tween_type {
+                    TweenType::MoveTo | TweenType::MoveFrom => {
+                        let to = vec3(t.params.x.unwrap_or(pos.x), t.params.y.unwrap_or(pos.y), pos.z) * BG_SCALE;
+                        if t.tween_type == TweenType::MoveTo {
+                            tween!(TransformPositionLens, Transform, x.2.translation, to, x.0);
+                        } else {
+                            tween!(TransformPositionLens, Transform, to, x.2.translation, x.0);
+                        }
+                    },
+                    TweenType::MoveBy | TweenType::MoveAdd => {
+                        let move_by = vec3(t.params.x.unwrap_or(0.), t.params.y.unwrap_or(0.), 0.) * BG_SCALE;
+                        let end = x.2.translation + move_by;
+                        tween!(TransformPositionLens, Transform, x.2.translation, end, x.0);
+                    },
+                    TweenType::RotateTo | TweenType::RotateFrom => {
+                        let (_, _, rz) = x.2.rotation.to_euler(EulerRot::XYZ);
+                        let end = Quat::from_rotation_z(t.params.z.map(|v| v.to_radians()).unwrap_or(rz));
+                        if t.tween_type == TweenType::RotateTo {
+                            tween!(TransformRotationLens, Transform, x.2.rotation, end, x.0);
+                        } else {
+                            tween!(TransformRotationLens, Transform, end, x.2.rotation, x.0);
+                        }
+                    },
+                    TweenType::RotateBy | TweenType::RotateAdd => {
+                        let d = Quat::from_rotation_z(t.params.z.map(|v| v.to_radians()).unwrap_or(0.));
+                        tween!(TransformRotationLens, Transform, x.2.rotation, x.2.rotation * d, x.0);
+                    },
+                    TweenType::PunchPosition | TweenType::ShakePosition
+                    | TweenType::PunchRotation | TweenType::ShakeRotation
+                    | TweenType::PunchScale | TweenType::ShakeScale => {
+                        spawn_shake(commands, x.0, &t.tween_type, absxyz!(0.), t.params.time, t.params.delay,
+                            (x.2.translation, x.2.rotation, x.2.scale), should_wait);
+                    },
+                    _ => {
+                        warn!("Unfinished tween type: {:?} for camera, use Zoom to scale", node.arg2)
+                    },
+                };
+            }
+        );
     } else {
         warn!("Unimplemented tween type: {:?}", node.arg2);
     }
@@ -0,0 +1,176 @@
+use bevy::prelude::*;
+use bevy::ui::{UiGlobalTransform, UiSystems};
+use bevy_spine38::prelude as spine38;
+use bevy_spine42::prelude as spine42;
+
+use crate::camera::VNCamera;
+use crate::cg_gallery::CgView;
+use crate::game::{SELECTTEXT, VNTexture, ViewRes};
+use crate::input_map::{Action, ActionState, update_actions};
+use crate::thumbnail::OffscreenSpine;
+
+// left stick deflection that moves the focus, and the delay before it repeats
+const STICK_THRESHOLD: f32 = 0.5;
+const STICK_REPEAT: f32 = 0.25;
+// world units per second at full right stick deflection
+const PAD_PAN_SPEED: f32 = 2000.;
+
+/// Button focused by gamepad navigation. Action::Press presses it for one frame,
+/// so the usual `Changed<Interaction>` handlers react as to a click.
+#[derive(Resource, Default)]
+struct GamepadFocus {
+    entity: Option<Entity>,
+    // released again on the next frame
+    pressed: Option<Entity>,
+}
+
+#[derive(Component)]
+struct FocusOutline;
+
+pub(crate) struct GamepadPlugin;
+
+impl Plugin for GamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GamepadFocus>()
+            // after bevy_ui so the press is not reset before the handlers see it
+            .add_systems(PreUpdate, gamepad_press.after(UiSystems::Focus).after(update_actions))
+            .add_systems(Update, (gamepad_navigate, gamepad_view));
+    }
+}
+
+fn gamepad_press(
+    actions: Res<ActionState>,
+    mut interaction_query: Query<&mut Interaction>,
+    mut focus: ResMut<GamepadFocus>,
+    view_res: Res<ViewRes>,
+) {
+    if let Some(entity) = focus.pressed.take() && let Ok(mut interaction) = interaction_query.get_mut(entity) {
+        interaction.set_if_neq(Interaction::None);
+    }
+    // the vn player reads Press in input_handler
+    if view_res.avg || !actions.just_pressed(Action::Press) {
+        return;
+    }
+    if let Some(entity) = focus.entity && let Ok(mut interaction) = interaction_query.get_mut(entity) {
+        *interaction = Interaction::Pressed;
+        focus.pressed = Some(entity);
+    }
+}
+
+// moves the focus to the nearest visible button in the pressed direction
+fn gamepad_navigate(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    gamepads: Query<&Gamepad>,
+    button_query: Query<(Entity, &ComputedNode, &UiGlobalTransform, &InheritedVisibility), With<Button>>,
+    parent_query: Query<&ChildOf>,
+    mut scroll_query: Query<(&Node, &mut ScrollPosition, &ComputedNode, &UiGlobalTransform), Without<Button>>,
+    mut focus: ResMut<GamepadFocus>,
+    mut stick_timer: Local<f32>,
+    time: Res<Time>,
+    view_res: Res<ViewRes>,
+) {
+    if view_res.avg {
+        return;
+    }
+    // ui coordinates grow downwards
+    let mut direction = Vec2::ZERO;
+    for (action, dir) in [
+        (Action::FocusUp, Vec2::NEG_Y),
+        (Action::FocusDown, Vec2::Y),
+        (Action::FocusLeft, Vec2::NEG_X),
+        (Action::FocusRight, Vec2::X),
+    ] {
+        if actions.just_pressed(action) {
+            direction = dir;
+        }
+    }
+    let stick = gamepads.iter().map(|gamepad| gamepad.left_stick()).find(|s| s.length() > STICK_THRESHOLD);
+    *stick_timer -= time.delta_secs();
+    match stick {
+        Some(stick) if direction == Vec2::ZERO && *stick_timer <= 0. => {
+            *stick_timer = STICK_REPEAT;
+            direction = if stick.x.abs() > stick.y.abs() {
+                Vec2::new(stick.x.signum(), 0.)
+            } else {
+                Vec2::new(0., -stick.y.signum())
+            };
+        }
+        None => *stick_timer = 0.,
+        _ => {}
+    }
+    if direction == Vec2::ZERO {
+        return;
+    }
+    let buttons: Vec<(Entity, Vec2)> = button_query.iter()
+        .filter(|(_, node, _, visibility)| visibility.get() && node.size() != Vec2::ZERO)
+        .map(|(entity, _, transform, _)| (entity, transform.translation))
+        .collect();
+    let current = focus.entity.and_then(|e| buttons.iter().find(|(b, _)| *b == e).copied());
+    let next = match current {
+        // start from the top left button
+        None => buttons.iter()
+            .min_by(|a, b| a.1.y.total_cmp(&b.1.y).then(a.1.x.total_cmp(&b.1.x)))
+            .map(|(entity, _)| *entity),
+        Some((current, from)) => buttons.iter()
+            .filter(|(entity, _)| *entity != current)
+            .filter_map(|(entity, to)| {
+                let offset = *to - from;
+                let along = offset.dot(direction);
+                // buttons off the axis are further away
+                (along > 0.).then(|| (*entity, along + (offset - direction * along).length() * 2.))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(entity, _)| entity),
+    };
+    let Some(next) = next else { return };
+    if let Some(previous) = focus.entity && let Ok(mut entity) = commands.get_entity(previous) {
+        entity.remove::<(Outline, FocusOutline)>();
+    }
+    commands.entity(next).insert((FocusOutline, Outline::new(px(3), Val::ZERO, SELECTTEXT)));
+    focus.entity = Some(next);
+    // scroll the focused entry into view of a scrolling parent
+    if let Ok((_, node, transform, _)) = button_query.get(next)
+        && let Ok(parent) = parent_query.get(next)
+        && let Ok((scroll_node, mut scroll_pos, view, view_transform)) = scroll_query.get_mut(parent.parent())
+        && scroll_node.overflow.y == OverflowAxis::Scroll {
+        let top = transform.translation.y - node.size().y / 2.;
+        let bottom = transform.translation.y + node.size().y / 2.;
+        let view_top = view_transform.translation.y - view.size().y / 2.;
+        let view_bottom = view_transform.translation.y + view.size().y / 2.;
+        if top < view_top {
+            scroll_pos.y -= (view_top - top) * view.inverse_scale_factor;
+        } else if bottom > view_bottom {
+            scroll_pos.y += (bottom - view_bottom) * view.inverse_scale_factor;
+        }
+    }
+}
+
+// right stick pans, LT and RT zoom around the center of the view
+fn gamepad_view(
+    gamepads: Query<&Gamepad>,
+    mut object_query: Query<&mut Transform, (Or<(With<spine38::Spine>, With<spine42::Spine>, With<VNTexture>, With<CgView>)>, Without<OffscreenSpine>)>,
+    camera: Single<&GlobalTransform, With<VNCamera>>,
+    time: Res<Time>,
+    view_res: Res<ViewRes>,
+) {
+    if view_res.avg {
+        return;
+    }
+    let delta_secs = time.delta_secs();
+    let pivot = camera.translation().truncate();
+    for gamepad in gamepads.iter() {
+        let pan = gamepad.right_stick() * PAD_PAN_SPEED * delta_secs;
+        let zoom = gamepad.get(GamepadButton::RightTrigger2).unwrap_or(0.)
+            - gamepad.get(GamepadButton::LeftTrigger2).unwrap_or(0.);
+        if pan == Vec2::ZERO && zoom == 0. {
+            continue;
+        }
+        let factor = 1. + zoom * delta_secs;
+        object_query.iter_mut().for_each(|mut obj| {
+            let offset = (obj.translation.truncate() + pan - pivot) * factor;
+            obj.translation = (pivot + offset).extend(obj.translation.z);
+            obj.scale *= factor;
+        });
+    }
+}
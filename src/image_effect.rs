@@ -0,0 +1,268 @@
+use bevy::asset::{load_internal_asset, uuid_handle};
+use bevy::prelude::*;
+use bevy::reflect::TypePath;
+use bevy::render::render_resource::{AsBindGroup, ShaderType};
+use bevy::shader::ShaderRef;
+use bevy::sprite_render::{AlphaMode2d, Material2d, Material2dPlugin};
+use bevy_spine38::prelude as spine38;
+use bevy_spine42::prelude as spine42;
+use bevy_tweening::{AnimTarget, Lens, TweenAnim};
+use std::time::Duration;
+
+use crate::game::{VNGui, VNSpine, VNTexture, WaitEffect, f32, should_wait, str};
+use crate::spine_facade::{AnySpine, HasSpine};
+use crate::utage4;
+
+const IMAGE_EFFECT_SHADER: Handle<Shader> = uuid_handle!("8e2b6d14-0c7a-4f95-b3e1-5a9d2c7f4e60");
+
+/// Image effects of a VNTexture or VNSpine layer, synced to the layer material every frame
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct ImageEffect {
+    grayscale: f32,
+    sepia: f32,
+    negative: f32,
+    blur: f32,
+    mosaic: f32,
+}
+
+impl ImageEffect {
+    pub(crate) fn is_none(&self) -> bool {
+        *self == Self::default()
+    }
+
+    // Utage effect name, returns false for unknown effects
+    pub(crate) fn set(&mut self, name: &str, value: f32) -> bool {
+        match name {
+            "Grayscale" => self.grayscale = value,
+            "Sepia" => self.sepia = value,
+            "Negative" | "NegaPosi" => self.negative = value,
+            "Blur" => self.blur = value,
+            "Mosaic" => self.mosaic = value,
+            _ => return false,
+        }
+        true
+    }
+
+    fn default_strength(name: &str) -> f32 {
+        match name {
+            "Blur" => 8.,
+            "Mosaic" => 16.,
+            _ => 1.,
+        }
+    }
+}
+
+// SpineImageEffect of both runtimes has the same fields
+macro_rules! spine_image_effect {
+    ($runtime:ident) => {
+        impl From<ImageEffect> for $runtime::SpineImageEffect {
+            fn from(effect: ImageEffect) -> Self {
+                Self {
+                    grayscale: effect.grayscale,
+                    sepia: effect.sepia,
+                    negative: effect.negative,
+                    blur: effect.blur,
+                    mosaic: effect.mosaic,
+                }
+            }
+        }
+    };
+}
+
+spine_image_effect!(spine38);
+spine_image_effect!(spine42);
+
+struct ImageEffectLens {
+    start: ImageEffect,
+    end: ImageEffect,
+}
+
+impl Lens<ImageEffect> for ImageEffectLens {
+    fn lerp(&mut self, mut target: Mut<ImageEffect>, ratio: f32) {
+        let lerp = |start: f32, end: f32| start + (end - start) * ratio;
+        target.grayscale = lerp(self.start.grayscale, self.end.grayscale);
+        target.sepia = lerp(self.start.sepia, self.end.sepia);
+        target.negative = lerp(self.start.negative, self.end.negative);
+        target.blur = lerp(self.start.blur, self.end.blur);
+        target.mosaic = lerp(self.start.mosaic, self.end.mosaic);
+    }
+}
+
+#[derive(ShaderType, Clone, Copy, Debug)]
+struct ImageEffectParams {
+    color: LinearRgba,
+    grayscale: f32,
+    sepia: f32,
+    negative: f32,
+    blur: f32,
+    mosaic: f32,
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Clone)]
+struct ImageEffectMaterial {
+    #[uniform(0)]
+    params: ImageEffectParams,
+    #[texture(1)]
+    #[sampler(2)]
+    image: Handle<Image>,
+}
+
+impl ImageEffectMaterial {
+    fn update(&mut self, effect: &ImageEffect, color: Color) {
+        self.params = ImageEffectParams {
+            color: color.to_linear(),
+            grayscale: effect.grayscale,
+            sepia: effect.sepia,
+            negative: effect.negative,
+            blur: effect.blur,
+            mosaic: effect.mosaic,
+        };
+    }
+}
+
+impl Material2d for ImageEffectMaterial {
+    fn fragment_shader() -> ShaderRef {
+        IMAGE_EFFECT_SHADER.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+}
+
+/* While an effect is active the sprite is shrunk to zero and a child mesh draws it instead,
+so transform and color tweens on the VNTexture keep working. */
+#[derive(Component)]
+struct ImageEffectMesh(Entity, Handle<ImageEffectMaterial>);
+
+pub(crate) struct ImageEffectPlugin;
+
+impl Plugin for ImageEffectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(Material2dPlugin::<ImageEffectMaterial>::default())
+            .add_systems(Update, (image_effect_texture, image_effect_spine));
+
+        load_internal_asset!(app, IMAGE_EFFECT_SHADER, "image_effect.wgsl", Shader::from_wgsl);
+    }
+}
+
+pub(crate) fn image_effect_cmd(
+    f: &str,
+    node: &utage4::Node,
+    commands: &mut Commands,
+    spine_query: &mut Query<(Entity, AnySpine, &mut VNSpine, &mut Transform), (Without<VNTexture>, Without<VNGui>)>,
+    tex_query: &mut Query<(Entity, &mut VNTexture, &mut Transform, &mut Sprite), (Without<VNSpine>, Without<VNGui>)>,
+    effect_query: &Query<&ImageEffect>,
+) {
+    // Arg1 target (all graphics if empty), Arg2 effect, Arg3 strength, Arg6 time
+    let name = str!(node.arg2);
+    // ImageEffectOff without effect name turns off every effect
+    let all_off = f == "ImageEffectOff" && node.arg2.is_none();
+    if !all_off && !ImageEffect::default().set(name, 0.) {
+        warn!("Unimplemented image effect: {}", name);
+        return
+    }
+    let strength = str!(node.arg3);
+    let value = if f == "ImageEffectOff" { 0. } else { f32!(strength, ImageEffect::default_strength(name)) };
+    f32!(time = node.arg6, 0.);
+    let targets = tex_query.iter()
+        .filter(|x| node.arg1.as_ref().is_none_or(|l| &x.1.1 == l || &x.1.2 == l))
+        .map(|x| x.0)
+        .chain(spine_query.iter()
+            .filter(|x| node.arg1.as_ref().is_none_or(|l| &x.2.0 == l || &x.2.2 == l))
+            .map(|x| x.0))
+        .collect::<Vec<_>>();
+    for target in targets {
+        let start = effect_query.get(target).copied().unwrap_or_default();
+        let mut end = start;
+        if all_off {
+            end = ImageEffect::default();
+        } else {
+            end.set(name, value);
+        }
+        info!("{} {} {} on {:?}", f, name, value, node.arg1);
+        if time > 0. {
+            let tween = bevy_tweening::Tween::new(
+                EaseFunction::Linear,
+                Duration::from_secs_f32(time),
+                ImageEffectLens { start, end },
+            );
+            commands.entity(target).insert(start);
+            let mut cmd = commands.spawn((
+                TweenAnim::new(tween),
+                AnimTarget::component::<ImageEffect>(target),
+            ));
+            if should_wait(&node.wait_type) {
+                cmd.insert(WaitEffect);
+            }
+        } else {
+            commands.entity(target).insert(end);
+        }
+    }
+}
+
+fn image_effect_texture(
+    mut commands: Commands,
+    mut tex_query: Query<(Entity, &ImageEffect, &mut Sprite, Option<&ImageEffectMesh>), With<VNTexture>>,
+    mut mesh_query: Query<&mut Transform, With<MeshMaterial2d<ImageEffectMaterial>>>,
+    mut materials: ResMut<Assets<ImageEffectMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    images: Res<Assets<Image>>,
+) {
+    for (entity, effect, mut sprite, effect_mesh) in tex_query.iter_mut() {
+        match effect_mesh {
+            Some(ImageEffectMesh(mesh, _)) if effect.is_none() => {
+                commands.entity(*mesh).despawn();
+                commands.entity(entity).remove::<ImageEffectMesh>();
+                sprite.custom_size = None;
+            }
+            Some(ImageEffectMesh(mesh, handle)) => {
+                if let Some(mut material) = materials.get_mut(handle) {
+                    material.update(effect, sprite.color);
+                }
+                if let (Some(image), Ok(mut transform)) = (images.get(&sprite.image), mesh_query.get_mut(*mesh)) {
+                    transform.scale = image.size_f32().extend(1.);
+                }
+            }
+            None if effect.is_none() => {}
+            None => {
+                let mut material = ImageEffectMaterial {
+                    params: ImageEffectParams {
+                        color: LinearRgba::WHITE,
+                        grayscale: 0.,
+                        sepia: 0.,
+                        negative: 0.,
+                        blur: 0.,
+                        mosaic: 0.,
+                    },
+                    image: sprite.image.clone(),
+                };
+                material.update(effect, sprite.color);
+                let handle = materials.add(material);
+                let mesh = commands.spawn((
+                    Mesh2d(meshes.add(Rectangle::new(1., 1.))),
+                    MeshMaterial2d(handle.clone()),
+                    Transform::from_xyz(0., 0., 0.),
+                    ChildOf(entity),
+                )).id();
+                commands.entity(entity).insert(ImageEffectMesh(mesh, handle));
+                sprite.custom_size = Some(Vec2::ZERO);
+            }
+        }
+    }
+}
+
+fn image_effect_spine(
+    mut commands: Commands,
+    spine_query: Query<(Entity, &ImageEffect, Has<spine38::Spine>), (HasSpine, Changed<ImageEffect>)>,
+) {
+    for (entity, effect, v38) in spine_query.iter() {
+        let mut entity = commands.entity(entity);
+        match (effect.is_none(), v38) {
+            (true, true) => entity.remove::<spine38::SpineImageEffect>(),
+            (true, false) => entity.remove::<spine42::SpineImageEffect>(),
+            (false, true) => entity.insert(spine38::SpineImageEffect::from(*effect)),
+            (false, false) => entity.insert(spine42::SpineImageEffect::from(*effect)),
+        };
+    }
+}
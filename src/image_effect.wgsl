@@ -0,0 +1,57 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+struct ImageEffectParams {
+    color: vec4<f32>,
+    grayscale: f32,
+    sepia: f32,
+    negative: f32,
+    blur: f32,
+    mosaic: f32,
+};
+
+@group(2) @binding(0)
+var<uniform> params: ImageEffectParams;
+@group(2) @binding(1)
+var texture: texture_2d<f32>;
+@group(2) @binding(2)
+var texture_sampler: sampler;
+
+// samples with premultiplied alpha so that blurred edges do not bleed color
+fn sample(uv: vec2<f32>) -> vec4<f32> {
+    let c = textureSampleLevel(texture, texture_sampler, uv, 0.0);
+    return vec4(c.rgb * c.a, c.a);
+}
+
+@fragment
+fn fragment(
+    input: VertexOutput,
+) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(texture));
+    var uv = input.uv;
+    if params.mosaic > 1.0 {
+        uv = (floor(uv * size / params.mosaic) + 0.5) * params.mosaic / size;
+    }
+    var tex = sample(uv);
+    if params.blur > 0.0 {
+        let offset = params.blur * 0.5 / size;
+        var sum = vec4(0.0);
+        for (var x = -2; x <= 2; x++) {
+            for (var y = -2; y <= 2; y++) {
+                sum += sample(uv + vec2(f32(x), f32(y)) * offset);
+            }
+        }
+        tex = sum / 25.0;
+    }
+
+    var rgb = tex.rgb / max(tex.a, 0.0001);
+    let gray = dot(rgb, vec3(0.299, 0.587, 0.114));
+    rgb = mix(rgb, vec3(gray), clamp(params.grayscale, 0.0, 1.0));
+    let sepia = vec3(
+        dot(rgb, vec3(0.393, 0.769, 0.189)),
+        dot(rgb, vec3(0.349, 0.686, 0.168)),
+        dot(rgb, vec3(0.272, 0.534, 0.131)),
+    );
+    rgb = mix(rgb, min(sepia, vec3(1.0)), clamp(params.sepia, 0.0, 1.0));
+    rgb = mix(rgb, vec3(1.0) - rgb, clamp(params.negative, 0.0, 1.0));
+    return vec4(rgb, tex.a) * params.color;
+}
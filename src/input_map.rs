@@ -0,0 +1,453 @@
+use bevy::input::InputSystems;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::read_to_string;
+
+use crate::debug_overlay::DebugOverlay;
+use crate::game::{FONT, HEADTEXT, HOVERBG, LISTTEXT, SELECTTEXT, ViewRes, Z_TEXT};
+use crate::scene_filter::SceneFilter;
+
+const KEYMAP_CONFIG: &str = "config.keymap.json";
+
+/// What the player wants to do, independent of the device
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub(crate) enum Action {
+    // vn player
+    Advance,
+    Skip,
+    Auto,
+    HideUi,
+    Exit,
+    Fullscreen,
+    // keyboard selection in the scene list and vn choices
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    // focus over every button, for gamepads
+    FocusUp,
+    FocusDown,
+    FocusLeft,
+    FocusRight,
+    Press,
+    // viewer
+    Search,
+    ResetView,
+    Pan,
+    Compare,
+    SyncTime,
+    NextSkeleton,
+    DebugOverlay,
+    Mute,
+    VolumeDown,
+    VolumeUp,
+    Keymap,
+    Settings,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+enum Binding {
+    Key(KeyCode),
+    // the key with Alt held
+    AltKey(KeyCode),
+    Mouse(MouseButton),
+    Pad(GamepadButton),
+}
+
+#[derive(PartialEq)]
+enum Device {
+    Keyboard,
+    Mouse,
+    Pad,
+}
+
+impl Binding {
+    fn device(&self) -> Device {
+        match self {
+            Binding::Key(_) | Binding::AltKey(_) => Device::Keyboard,
+            Binding::Mouse(_) => Device::Mouse,
+            Binding::Pad(_) => Device::Pad,
+        }
+    }
+}
+
+impl std::fmt::Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{:?}", key),
+            Binding::AltKey(key) => write!(f, "Alt+{:?}", key),
+            Binding::Mouse(button) => write!(f, "Mouse {:?}", button),
+            Binding::Pad(button) => write!(f, "Pad {:?}", button),
+        }
+    }
+}
+
+/// Bindings of every action, saved to KEYMAP_CONFIG on change
+#[derive(Resource, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct InputMap {
+    bindings: BTreeMap<Action, Vec<Binding>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        use Binding::*;
+        Self {
+            bindings: BTreeMap::from([
+                (Action::Advance, vec![Mouse(MouseButton::Left), Key(KeyCode::Enter), Key(KeyCode::Space), Pad(GamepadButton::South)]),
+                (Action::Skip, vec![Key(KeyCode::ControlLeft), Key(KeyCode::ControlRight), Pad(GamepadButton::RightTrigger)]),
+                (Action::Auto, vec![Key(KeyCode::Tab), Pad(GamepadButton::LeftTrigger)]),
+                (Action::HideUi, vec![Mouse(MouseButton::Right), Pad(GamepadButton::North)]),
+                (Action::Exit, vec![Key(KeyCode::Escape), Pad(GamepadButton::East)]),
+                (Action::Fullscreen, vec![AltKey(KeyCode::Enter), Key(KeyCode::F11)]),
+                (Action::Up, vec![Key(KeyCode::ArrowUp)]),
+                (Action::Down, vec![Key(KeyCode::ArrowDown)]),
+                (Action::Left, vec![Key(KeyCode::ArrowLeft)]),
+                (Action::Right, vec![Key(KeyCode::ArrowRight)]),
+                (Action::Confirm, vec![Key(KeyCode::Enter), Key(KeyCode::Space)]),
+                (Action::FocusUp, vec![Pad(GamepadButton::DPadUp)]),
+                (Action::FocusDown, vec![Pad(GamepadButton::DPadDown)]),
+                (Action::FocusLeft, vec![Pad(GamepadButton::DPadLeft)]),
+                (Action::FocusRight, vec![Pad(GamepadButton::DPadRight)]),
+                (Action::Press, vec![Pad(GamepadButton::South)]),
+                (Action::Search, vec![Key(KeyCode::Slash)]),
+                (Action::ResetView, vec![Key(KeyCode::KeyR), Key(KeyCode::Home)]),
+                (Action::Pan, vec![Mouse(MouseButton::Middle)]),
+                (Action::Compare, vec![Key(KeyCode::KeyC)]),
+                (Action::SyncTime, vec![Key(KeyCode::KeyT)]),
+                (Action::NextSkeleton, vec![Key(KeyCode::KeyN)]),
+                (Action::DebugOverlay, vec![Key(KeyCode::F3)]),
+                (Action::Mute, vec![Key(KeyCode::KeyM)]),
+                (Action::VolumeDown, vec![Key(KeyCode::Minus)]),
+                (Action::VolumeUp, vec![Key(KeyCode::Equal)]),
+                (Action::Keymap, vec![Key(KeyCode::F1)]),
+                (Action::Settings, vec![Key(KeyCode::F2)]),
+            ]),
+        }
+    }
+}
+
+impl InputMap {
+    /// Bindings of an action as shown to the user, e.g. "Key(Slash)" prints as "Slash"
+    pub(crate) fn describe(&self, action: Action) -> String {
+        self.bindings.get(&action)
+            .map(|bindings| bindings.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn load() -> Self {
+        let mut map: Self = read_to_string(KEYMAP_CONFIG).ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        // actions added after the file was written keep their defaults
+        for (action, bindings) in Self::default().bindings {
+            map.bindings.entry(action).or_insert(bindings);
+        }
+        map
+    }
+
+    pub(crate) fn save(&self) {
+        match serde_json::to_string_pretty(self) {
+            Ok(content) => if let Err(e) = std::fs::write(KEYMAP_CONFIG, content) {
+                warn!("failed to save {}: {}", KEYMAP_CONFIG, e);
+            },
+            Err(e) => warn!("failed to serialize keymap: {}", e),
+        }
+    }
+}
+
+/// Actions triggered this frame, read by the input systems instead of raw devices
+#[derive(Resource, Default)]
+pub(crate) struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    just_released: HashSet<Action>,
+}
+
+impl ActionState {
+    pub(crate) fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub(crate) fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub(crate) fn just_released(&self, action: Action) -> bool {
+        self.just_released.contains(&action)
+    }
+}
+
+/// The rebinding screen, capturing the next input for an action
+#[derive(Resource, Default)]
+pub(crate) struct KeymapScreen {
+    open: bool,
+    capturing: Option<Action>,
+}
+
+#[derive(Component)]
+struct KeymapPanel;
+
+#[derive(Component)]
+struct KeymapRow(Action);
+
+#[derive(Component)]
+struct KeymapText(Action);
+
+#[derive(Component)]
+struct KeymapReset;
+
+pub(crate) struct InputMapPlugin;
+
+impl Plugin for InputMapPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(InputMap::load())
+            .init_resource::<ActionState>()
+            .init_resource::<KeymapScreen>()
+            .add_systems(PreUpdate, update_actions.after(InputSystems))
+            .add_systems(Update, (toggle_keymap_screen, capture_binding, keymap_rows, keymap_texts, save_input_map).chain());
+    }
+}
+
+pub(crate) fn update_actions(
+    key: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    map: Res<InputMap>,
+    screen: Res<KeymapScreen>,
+    filter: Res<SceneFilter>,
+    mut actions: ResMut<ActionState>,
+) {
+    actions.pressed.clear();
+    actions.just_pressed.clear();
+    actions.just_released.clear();
+    // the next input goes to the rebinding screen
+    if screen.capturing.is_some() {
+        return;
+    }
+    let alt = key.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    for (&action, bindings) in map.bindings.iter() {
+        let (mut pressed, mut just_pressed, mut just_released) = (false, false, false);
+        for binding in bindings {
+            let state = match *binding {
+                // typing in the search box is not an action
+                Binding::Key(_) | Binding::AltKey(_) if filter.focused => continue,
+                Binding::Key(k) => (key.pressed(k), key.just_pressed(k) && !alt, key.just_released(k)),
+                Binding::AltKey(k) => (key.pressed(k) && alt, key.just_pressed(k) && alt, key.just_released(k)),
+                Binding::Mouse(b) => (mouse.pressed(b), mouse.just_pressed(b), mouse.just_released(b)),
+                Binding::Pad(b) => (
+                    gamepads.iter().any(|g| g.pressed(b)),
+                    gamepads.iter().any(|g| g.just_pressed(b)),
+                    gamepads.iter().any(|g| g.just_released(b)),
+                ),
+            };
+            pressed |= state.0;
+            just_pressed |= state.1;
+            just_released |= state.2;
+        }
+        if pressed {
+            actions.pressed.insert(action);
+        }
+        if just_pressed {
+            actions.just_pressed.insert(action);
+        }
+        // released only once no other binding holds it
+        if just_released && !pressed {
+            actions.just_released.insert(action);
+        }
+    }
+}
+
+fn toggle_keymap_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    actions: Res<ActionState>,
+    panel_query: Query<Entity, With<KeymapPanel>>,
+    map: Res<InputMap>,
+    mut screen: ResMut<KeymapScreen>,
+    view_res: Res<ViewRes>,
+) {
+    if !actions.just_pressed(Action::Keymap) && !(screen.open && actions.just_pressed(Action::Exit)) {
+        return;
+    }
+    screen.open = !screen.open && !view_res.avg;
+    screen.capturing = None;
+    panel_query.iter().for_each(|entity| {
+        commands.entity(entity).despawn()
+    });
+    if !screen.open {
+        return;
+    }
+    let font = |size: f32| TextFont {
+        font: asset_server.load(FONT).into(),
+        font_size: FontSize::Px(size),
+        ..default()
+    };
+    commands.spawn((
+        Visibility::Visible,
+        KeymapPanel,
+        ZIndex(Z_TEXT),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(40.),
+            left: Val::Percent(30.),
+            top: Val::Percent(5.),
+            padding: UiRect::all(px(20)),
+            row_gap: px(4),
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0., 0., 0., 0.9)),
+    )).with_children(|parent| {
+        parent.spawn((
+            Text::new("Key Bindings"),
+            font(42.),
+            TextColor(HEADTEXT),
+        ));
+        parent.spawn((
+            Text::new("Click an action, then press a key, mouse or pad button to replace the bindings of that device. Backspace clears all, Esc cancels."),
+            font(24.),
+            TextColor(SELECTTEXT),
+        ));
+        for &action in map.bindings.keys() {
+            // text is filled in by keymap_texts
+            parent.spawn((
+                Button,
+                KeymapRow(action),
+                Node {
+                    padding: UiRect::horizontal(px(6)),
+                    ..default()
+                },
+                BackgroundColor(Color::NONE),
+            )).with_child((
+                KeymapText(action),
+                Text::new(""),
+                font(28.),
+                TextColor(LISTTEXT),
+            ));
+        }
+        parent.spawn((
+            Button,
+            KeymapReset,
+            Text::new("Reset to defaults"),
+            font(30.),
+            TextColor(HEADTEXT),
+            BackgroundColor(Color::NONE),
+        ));
+    });
+}
+
+// runs before keymap_rows so the click that starts capturing is not captured
+fn capture_binding(
+    mut keyboard: MessageReader<KeyboardInput>,
+    key: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    mut map: ResMut<InputMap>,
+    mut screen: ResMut<KeymapScreen>,
+) {
+    let Some(action) = screen.capturing else {
+        keyboard.clear();
+        return;
+    };
+    let alt = key.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    let mut binding = None;
+    for ev in keyboard.read() {
+        if !ev.state.is_pressed() {
+            continue;
+        }
+        match ev.key_code {
+            KeyCode::Escape => {
+                screen.capturing = None;
+                return;
+            }
+            KeyCode::Backspace => {
+                map.bindings.insert(action, vec!());
+                screen.capturing = None;
+                return;
+            }
+            // wait for the key pressed with Alt
+            KeyCode::AltLeft | KeyCode::AltRight => {}
+            k if alt => binding = Some(Binding::AltKey(k)),
+            k => binding = Some(Binding::Key(k)),
+        }
+    }
+    binding = binding
+        .or_else(|| mouse.get_just_pressed().next().map(|&b| Binding::Mouse(b)))
+        .or_else(|| gamepads.iter().find_map(|g| g.get_just_pressed().next().map(|&b| Binding::Pad(b))));
+    if let Some(binding) = binding {
+        info!("bind {:?} to {}", action, binding);
+        if let Some(other) = map.bindings.iter()
+            .find(|(a, bindings)| **a != action && bindings.contains(&binding))
+            .map(|(a, _)| *a) {
+            warn!("{} is also bound to {:?}", binding, other);
+        }
+        // the bindings of other devices are kept, so rebinding a key leaves gamepad play working
+        let bindings = map.bindings.entry(action).or_default();
+        bindings.retain(|b| b.device() != binding.device());
+        bindings.push(binding);
+        screen.capturing = None;
+    }
+}
+
+fn keymap_rows(
+    mut row_query: Query<(&Interaction, &KeymapRow, &mut BackgroundColor), (Changed<Interaction>, Without<KeymapReset>)>,
+    mut reset_query: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<KeymapReset>)>,
+    mut map: ResMut<InputMap>,
+    mut screen: ResMut<KeymapScreen>,
+) {
+    row_query.iter_mut().for_each(|(interaction, row, mut bg_color)| {
+        match *interaction {
+            Interaction::Pressed => {
+                screen.capturing = Some(row.0);
+            }
+            Interaction::Hovered => {
+                *bg_color = HOVERBG.into();
+            }
+            _ => {
+                *bg_color = Color::NONE.into();
+            }
+        }
+    });
+    reset_query.iter_mut().for_each(|(interaction, mut bg_color)| {
+        match *interaction {
+            Interaction::Pressed => {
+                *map = InputMap::default();
+            }
+            Interaction::Hovered => {
+                *bg_color = HOVERBG.into();
+            }
+            _ => {
+                *bg_color = Color::NONE.into();
+            }
+        }
+    });
+}
+
+fn keymap_texts(
+    mut text_query: Query<(&mut Text, &KeymapText)>,
+    added: Query<(), Added<KeymapText>>,
+    map: Res<InputMap>,
+    screen: Res<KeymapScreen>,
+) {
+    if !map.is_changed() && !screen.is_changed() && added.is_empty() {
+        return;
+    }
+    for (mut text, row) in text_query.iter_mut() {
+        let bindings = if screen.capturing == Some(row.0) {
+            "press an input...".to_string()
+        } else {
+            map.describe(row.0)
+        };
+        text.0 = format!("{:?}: {}", row.0, bindings);
+    }
+}
+
+fn save_input_map(map: Res<InputMap>) {
+    if map.is_changed() && !map.is_added() {
+        map.save();
+    }
+}
@@ -0,0 +1,180 @@
+use bevy::audio::{AddAudioSource, AudioSource, Decodable, PlaybackMode, Source};
+use bevy::prelude::*;
+use std::time::Duration;
+
+type SourceDecoder = <AudioSource as Decodable>::Decoder;
+type SourceSample = <SourceDecoder as Iterator>::Item;
+
+/// Audio that plays once from the start, then loops from the intro time forever
+#[derive(Asset, TypePath)]
+pub struct IntroLoopSource {
+    source: AudioSource,
+    intro: Duration,
+}
+
+impl Decodable for IntroLoopSource {
+    type DecoderItem = SourceSample;
+    type Decoder = IntroLoopDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        IntroLoopDecoder {
+            source: self.source.clone(),
+            intro: self.intro,
+            decoder: self.source.decoder(),
+        }
+    }
+}
+
+pub struct IntroLoopDecoder {
+    source: AudioSource,
+    intro: Duration,
+    decoder: SourceDecoder,
+}
+
+impl IntroLoopDecoder {
+    // start a new decoder at the loop point, the seek happens inside next() so there is no gap.
+    // intro_loop_ready only builds the source for formats that can seek
+    fn restart(&mut self) {
+        let mut decoder = self.source.decoder();
+        let _ = decoder.try_seek(self.intro);
+        self.decoder = decoder;
+    }
+}
+
+impl Iterator for IntroLoopDecoder {
+    type Item = SourceSample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(sample) = self.decoder.next() {
+            return Some(sample);
+        }
+        self.restart();
+        self.decoder.next()
+    }
+}
+
+impl Source for IntroLoopDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.decoder.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.decoder.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.decoder.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Waits for the audio file to load, then gets replaced by an `AudioPlayer<IntroLoopSource>`.
+/// Use `PlaybackMode::Once`, the decoder never ends by itself.
+/// Formats that can not seek loop from the start with a plain `AudioPlayer` instead.
+#[derive(Component)]
+pub struct IntroLoop {
+    pub source: Handle<AudioSource>,
+    pub intro: Duration,
+}
+
+pub struct IntroLoopPlugin;
+
+impl Plugin for IntroLoopPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_source::<IntroLoopSource>()
+            .add_systems(Update, intro_loop_ready);
+    }
+}
+
+fn intro_loop_ready(
+    mut commands: Commands,
+    query: Query<(Entity, &IntroLoop, &PlaybackSettings)>,
+    sources: Res<Assets<AudioSource>>,
+    mut loops: ResMut<Assets<IntroLoopSource>>,
+) {
+    for (entity, intro_loop, settings) in query.iter() {
+        let Some(source) = sources.get(&intro_loop.source) else { continue };
+        let mut entity = commands.entity(entity);
+        entity.remove::<IntroLoop>();
+        if source.decoder().try_seek(intro_loop.intro).is_err() {
+            warn!("{:?} can not seek, looping from the start", intro_loop.source.path());
+            entity.insert((
+                AudioPlayer(intro_loop.source.clone()),
+                PlaybackSettings {
+                    mode: PlaybackMode::Loop,
+                    ..*settings
+                },
+            ));
+            continue;
+        }
+        let handle = loops.add(IntroLoopSource {
+            source: source.clone(),
+            intro: intro_loop.intro,
+        });
+        entity.insert(AudioPlayer(handle));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 8000;
+
+    // half a second of mono 16 bit PCM, each sample holds its own index
+    fn wav() -> AudioSource {
+        let data: Vec<u8> = (0..RATE as i16 / 2).flat_map(i16::to_le_bytes).collect();
+        let mut bytes = Vec::new();
+        bytes.extend(b"RIFF");
+        bytes.extend((36 + data.len() as u32).to_le_bytes());
+        bytes.extend(b"WAVEfmt ");
+        bytes.extend(16u32.to_le_bytes());
+        // PCM, mono, sample rate, byte rate, block align, bits per sample
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend(RATE.to_le_bytes());
+        bytes.extend((RATE * 2).to_le_bytes());
+        bytes.extend(2u16.to_le_bytes());
+        bytes.extend(16u16.to_le_bytes());
+        bytes.extend(b"data");
+        bytes.extend((data.len() as u32).to_le_bytes());
+        bytes.extend(data);
+        AudioSource { bytes: bytes.into() }
+    }
+
+    fn intro_loop(intro: Duration) -> (Vec<SourceSample>, IntroLoopDecoder) {
+        let source = wav();
+        let plain = source.decoder().collect();
+        (plain, IntroLoopSource { source, intro }.decoder())
+    }
+
+    #[test]
+    fn loops_from_intro() {
+        let (plain, decoder) = intro_loop(Duration::from_millis(250));
+        assert_eq!(plain.len(), 4000);
+        let played: Vec<_> = decoder.take(4000 + 2000 * 2).collect();
+        assert_eq!(played[..4000], plain[..]);
+        // every loop seeks to the sample at 250ms
+        assert_eq!(played[4000..6000], plain[2000..]);
+        assert_eq!(played[6000..], plain[2000..]);
+    }
+
+    #[test]
+    fn loops_from_start() {
+        let (plain, decoder) = intro_loop(Duration::ZERO);
+        let played: Vec<_> = decoder.take(4000 * 2).collect();
+        assert_eq!(played[..4000], plain[..]);
+        assert_eq!(played[4000..], plain[..]);
+    }
+
+    #[test]
+    fn never_ends() {
+        let (_, decoder) = intro_loop(Duration::from_millis(400));
+        assert_eq!(decoder.total_duration(), None);
+        assert_eq!(decoder.channels(), 1);
+        assert_eq!(decoder.sample_rate(), RATE);
+    }
+}
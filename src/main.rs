@@ -2,18 +2,59 @@
 #![allow(clippy::too_many_arguments)]
 #![allow(clippy::type_complexity)]
 
-use std::path::Path;
-
+mod anime_control;
+mod audio_path;
+mod camera;
+mod cg_gallery;
+mod chapter;
+mod cli;
+mod compare;
+mod debug_overlay;
+mod export;
+mod gamepad;
+mod image_effect;
+mod input_map;
+mod intro_loop;
+mod mixer;
+mod profile;
+mod scene_filter;
+mod settings;
+mod skin_panel;
+mod spine_export;
+mod spine_facade;
+mod spine_tween;
+mod spine_version;
+mod thumbnail;
+mod transition;
 mod tween;
 mod utage4;
-mod monmusu;
-mod rlyeh;
+mod game;
+
+use profile::GameProfile;
 
 fn main() {
-    let p = format!("assets/advscene/resources/advscene/sound/voice/{}", monmusu::get_intro());
-    if Path::new(&p).exists() {
-        monmusu::play();
-    } else {
-        rlyeh::play();
+    let args: Vec<String> = std::env::args().collect();
+    let (options, export) = match cli::LaunchOptions::from_args(&args)
+        .and_then(|options| Ok((options, export::ExportRequest::from_args(&args)?))) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    if let Some(assets) = options.assets.clone() {
+        cli::set_assets_root(assets);
+    }
+    let profile = match options.profile.as_deref().map_or_else(GameProfile::detect, GameProfile::load) {
+        Ok(profile) => profile,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    profile::set_profile(profile);
+    match export {
+        Some(request) => spine_export::export(request),
+        None => game::play(options),
     }
 }
@@ -0,0 +1,185 @@
+use bevy::audio::Volume;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+
+use crate::game::{AudioFade, AudioType, VNAudio};
+use crate::input_map::{Action, ActionState};
+use crate::scene_filter::SceneFilter;
+use crate::spine_facade::SpineAudio;
+
+const MIXER_CONFIG: &str = "config.mixer.json";
+// seconds for ducking to reach its level
+const DUCKING_TIME: f32 = 0.3;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct MixerChannel {
+    pub(crate) volume: f32,
+    pub(crate) mute: bool,
+}
+
+impl Default for MixerChannel {
+    fn default() -> Self {
+        Self {
+            volume: 1.,
+            mute: false,
+        }
+    }
+}
+
+impl MixerChannel {
+    fn gain(&self) -> f32 {
+        if self.mute { 0. } else { self.volume }
+    }
+}
+
+/// Master and per AudioType volume, saved to MIXER_CONFIG on change
+#[derive(Resource, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct AudioMixer {
+    pub(crate) master: MixerChannel,
+    pub(crate) bgm: MixerChannel,
+    pub(crate) se: MixerChannel,
+    pub(crate) ambience: MixerChannel,
+    pub(crate) voice: MixerChannel,
+    // bgm and ambience volume while a voice plays
+    ducking: f32,
+    // voice volume per speaker CharacterName
+    pub(crate) characters: BTreeMap<String, MixerChannel>,
+    // a new line only cuts off the same speaker's voice
+    pub(crate) keep_voice: bool,
+}
+
+impl Default for AudioMixer {
+    fn default() -> Self {
+        Self {
+            master: default(),
+            bgm: default(),
+            se: default(),
+            ambience: default(),
+            voice: default(),
+            ducking: 0.5,
+            characters: BTreeMap::new(),
+            keep_voice: false,
+        }
+    }
+}
+
+impl AudioMixer {
+    pub(crate) fn load() -> Self {
+        read_to_string(MIXER_CONFIG).ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn save(&self) {
+        match serde_json::to_string_pretty(self) {
+            Ok(content) => if let Err(e) = std::fs::write(MIXER_CONFIG, content) {
+                warn!("failed to save {}: {}", MIXER_CONFIG, e);
+            },
+            Err(e) => warn!("failed to serialize mixer: {}", e),
+        }
+    }
+
+    fn channel(&self, audio_type: &AudioType) -> &MixerChannel {
+        match audio_type {
+            AudioType::Bgm => &self.bgm,
+            AudioType::Se => &self.se,
+            AudioType::Ambience => &self.ambience,
+            AudioType::Voice => &self.voice,
+        }
+    }
+
+    fn gain(&self, audio_type: &AudioType) -> f32 {
+        self.master.gain() * self.channel(audio_type).gain()
+    }
+
+    fn audio_gain(&self, audio: &VNAudio) -> f32 {
+        let speaker = match audio.0 {
+            AudioType::Voice => self.characters.get(&audio.1).map_or(1., MixerChannel::gain),
+            _ => 1.,
+        };
+        self.gain(&audio.0) * speaker
+    }
+}
+
+/// Volume given by the script, before mixing
+#[derive(Component)]
+struct MixerVolume(f32);
+
+pub(crate) struct MixerPlugin;
+
+impl Plugin for MixerPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AudioMixer::load())
+            .add_observer(mix_new_audio)
+            .add_systems(Update, (mixer_input, (mix_audio, save_mixer).after(mixer_input)));
+    }
+}
+
+// mix before the sink is created so new sounds never start at the raw volume
+fn mix_new_audio(
+    add: On<Add, VNAudio>,
+    mut commands: Commands,
+    mut query: Query<(&VNAudio, &mut PlaybackSettings)>,
+    mixer: Res<AudioMixer>,
+) {
+    if let Ok((audio, mut settings)) = query.get_mut(add.entity) {
+        let volume = settings.volume.to_linear();
+        settings.volume = Volume::Linear(volume * mixer.audio_gain(audio));
+        commands.entity(add.entity).insert(MixerVolume(volume));
+    }
+}
+
+fn mix_audio(
+    mut sink_query: Query<(&mut AudioSink, &VNAudio, &MixerVolume), Without<AudioFade>>,
+    mut ducking: Local<Option<f32>>,
+    mut spine_audio: SpineAudio,
+    mixer: Res<AudioMixer>,
+    time: Res<Time>,
+) {
+    // spine event sounds are mixed as se when they start
+    if mixer.is_changed() {
+        spine_audio.set_volume(mixer.gain(&AudioType::Se));
+    }
+    let voice_playing = sink_query.iter()
+        .any(|(sink, audio, _)| audio.0 == AudioType::Voice && !sink.is_paused() && !sink.empty());
+    let target = if voice_playing { mixer.ducking } else { 1. };
+    let level = ducking.get_or_insert(1.);
+    let step = (1. - mixer.ducking).max(0.01) * time.delta_secs() / DUCKING_TIME;
+    *level = if *level < target { (*level + step).min(target) } else { (*level - step).max(target) };
+    for (mut sink, audio, volume) in sink_query.iter_mut() {
+        let duck = if matches!(audio.0, AudioType::Bgm | AudioType::Ambience) { *level } else { 1. };
+        sink.set_volume(Volume::Linear(volume.0 * mixer.audio_gain(audio) * duck));
+    }
+}
+
+fn mixer_input(
+    actions: Res<ActionState>,
+    mut mixer: ResMut<AudioMixer>,
+    filter: Res<SceneFilter>,
+) {
+    if filter.focused {
+        return;
+    }
+    if actions.just_pressed(Action::Mute) {
+        mixer.master.mute = !mixer.master.mute;
+        info!("master mute {}", mixer.master.mute);
+    }
+    if actions.just_pressed(Action::VolumeDown) {
+        mixer.master.volume = (mixer.master.volume - 0.1).max(0.);
+        info!("master volume {:.1}", mixer.master.volume);
+    }
+    if actions.just_pressed(Action::VolumeUp) {
+        mixer.master.volume = (mixer.master.volume + 0.1).min(1.);
+        info!("master volume {:.1}", mixer.master.volume);
+    }
+}
+
+fn save_mixer(mixer: Res<AudioMixer>) {
+    if mixer.is_changed() && !mixer.is_added() {
+        mixer.save();
+    }
+}
@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs::{read_dir, read_to_string};
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::cli::asset_path;
+
+const PROFILE_DIR: &str = "profiles";
+// built into the binary, a file of the same name in PROFILE_DIR takes precedence
+const BUILTIN: [(&str, &str); 2] = [
+    ("monmusu", include_str!("../profiles/monmusu.json")),
+    ("rlyeh", include_str!("../profiles/rlyeh.json")),
+];
+// random characters tried before the fallback
+const INTRO_TRIES: usize = 10;
+
+static PROFILE: OnceLock<GameProfile> = OnceLock::new();
+
+/// Sets the profile of this run, only the first call has an effect
+pub fn set_profile(profile: GameProfile) {
+    let _ = PROFILE.set(profile);
+}
+
+pub fn profile() -> &'static GameProfile {
+    PROFILE.get().expect("the game profile is set before the app starts")
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SpineRuntime {
+    #[serde(rename = "3.8")]
+    V38,
+    #[serde(rename = "4.2")]
+    V42,
+}
+
+/// sRGB colors of the message window and choices
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Palette {
+    pub char_text: [u8; 3],
+    pub vn_text: [u8; 3],
+    pub select_bg: [u8; 3],
+    pub select_border: [u8; 3],
+}
+
+/// Positions in the GUI layer, y grows upwards from the center
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MessageWindowLayout {
+    pub image: String,
+    pub alpha: f32,
+    pub y: f32,
+    // left of the speaker name
+    pub name: [f32; 2],
+    // top left of the dialogue text
+    pub text: [f32; 2],
+}
+
+/// The voice played when the browser opens, relative to the voice folder
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntroVoice {
+    Fixed(String),
+    /// `{id}` is a random character in 1..=`characters` padded to 4 digits and `{variant}` is in 1..=`variants`.
+    /// Characters without the file are skipped, `fallback` is used after a few tries.
+    Random {
+        pattern: String,
+        characters: u32,
+        variants: u32,
+        fallback: u32,
+    },
+}
+
+fn default_audio_extensions() -> Vec<String> {
+    ["m4a", "ogg", "wav"].map(String::from).to_vec()
+}
+
+/// Everything that differs between titles
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameProfile {
+    #[serde(skip)]
+    pub name: String,
+    // runtime of skeletons whose header has no version
+    pub spine: SpineRuntime,
+    // asset path only present in this title, picks the profile when --profile is not given
+    #[serde(default)]
+    pub detect: Option<String>,
+    pub font: String,
+    pub palette: Palette,
+    // folders of bgm/se/voice and bg/event/rule/sprite/thumbnail
+    pub sound: String,
+    pub texture: String,
+    pub ambience: String,
+    // tried in order when resolving audio file names
+    #[serde(default = "default_audio_extensions")]
+    pub audio_extensions: Vec<String>,
+    pub message_window: MessageWindowLayout,
+    pub intro: IntroVoice,
+}
+
+impl GameProfile {
+    /// Built-in profiles and the files in PROFILE_DIR, sorted by name
+    pub fn names() -> Vec<String> {
+        let files = read_dir(PROFILE_DIR).into_iter().flatten().flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                (path.extension()? == "json").then(|| path.file_stem()?.to_str().map(ToString::to_string))?
+            });
+        BUILTIN.iter().map(|(name, _)| name.to_string())
+            .chain(files)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    pub fn load(name: &str) -> Result<Self, String> {
+        let path = Path::new(PROFILE_DIR).join(format!("{}.json", name));
+        let content = match read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => BUILTIN.iter()
+                .find(|(builtin, _)| *builtin == name)
+                .map(|(_, content)| content.to_string())
+                .ok_or(format!("unknown profile {}, expected one of {}", name, Self::names().join(", ")))?,
+        };
+        let mut profile: Self = serde_json::from_str(&content).map_err(|e| format!("profile {}: {}", name, e))?;
+        profile.name = name.to_string();
+        Ok(profile)
+    }
+
+    /// The first profile whose `detect` file exists, else the first one without `detect`
+    pub fn detect() -> Result<Self, String> {
+        let profiles: Vec<Self> = Self::names().iter().map(|name| Self::load(name)).collect::<Result<_, _>>()?;
+        let found = profiles.iter()
+            .position(|p| p.detect.as_ref().is_some_and(|file| asset_path(file).exists()))
+            .or_else(|| profiles.iter().position(|p| p.detect.is_none()))
+            .ok_or("no profile matches the assets, pick one with --profile")?;
+        Ok(profiles.into_iter().nth(found).unwrap())
+    }
+
+    pub fn intro_voice(&self) -> String {
+        match &self.intro {
+            IntroVoice::Fixed(file) => file.clone(),
+            IntroVoice::Random { pattern, characters, variants, fallback } => {
+                let voice = |id: u128, variant: u128| pattern
+                    .replace("{id}", &format!("{:04}", id))
+                    .replace("{variant}", &variant.to_string());
+                let micros = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_micros();
+                let mut seed = micros;
+                let variant = (micros % (*variants).max(1) as u128) + 1;
+                for _ in 0..INTRO_TRIES {
+                    // SplitMix64
+                    seed = seed.wrapping_add(0x9E3779B97F4A7C15);
+                    let mut x = seed;
+                    x = (x ^ (x >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+                    x = (x ^ (x >> 27)).wrapping_mul(0x94D049BB133111EB);
+                    let random_val = x ^ (x >> 31);
+                    let id = (random_val % (*characters).max(1) as u128) + 1;
+                    let p = voice(id, variant);
+                    if asset_path(format!("{}voice/{}", self.sound, p)).exists() {
+                        return p;
+                    }
+                }
+                voice(*fallback as u128, variant)
+            }
+        }
+    }
+}
@@ -0,0 +1,30 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+struct RuleFadeParams {
+    color: vec4<f32>,
+    progress: f32,
+    softness: f32,
+};
+
+@group(2) @binding(0)
+var<uniform> params: RuleFadeParams;
+@group(2) @binding(1)
+var texture: texture_2d<f32>;
+@group(2) @binding(2)
+var texture_sampler: sampler;
+@group(2) @binding(3)
+var rule_texture: texture_2d<f32>;
+@group(2) @binding(4)
+var rule_sampler: sampler;
+
+@fragment
+fn fragment(
+    input: VertexOutput,
+) -> @location(0) vec4<f32> {
+    let tex_color = textureSample(texture, texture_sampler, input.uv) * params.color;
+    // dark pixels of the rule image are revealed first
+    let value = textureSample(rule_texture, rule_sampler, input.uv).r;
+    let softness = max(params.softness, 0.0001);
+    let coverage = clamp((params.progress * (1.0 + softness) - value) / softness, 0.0, 1.0);
+    return vec4(tex_color.rgb, tex_color.a * coverage);
+}
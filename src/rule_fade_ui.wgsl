@@ -0,0 +1,25 @@
+#import bevy_ui::ui_vertex_output::UiVertexOutput
+
+struct RuleFadeParams {
+    color: vec4<f32>,
+    progress: f32,
+    softness: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> params: RuleFadeParams;
+@group(1) @binding(1)
+var rule_texture: texture_2d<f32>;
+@group(1) @binding(2)
+var rule_sampler: sampler;
+
+@fragment
+fn fragment(
+    input: UiVertexOutput,
+) -> @location(0) vec4<f32> {
+    // dark pixels of the rule image are covered first
+    let value = textureSample(rule_texture, rule_sampler, input.uv).r;
+    let softness = max(params.softness, 0.0001);
+    let coverage = clamp((params.progress * (1.0 + softness) - value) / softness, 0.0, 1.0);
+    return vec4(params.color.rgb, params.color.a * coverage);
+}
@@ -0,0 +1,269 @@
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+use bevy::ui::UiGlobalTransform;
+use regex::Regex;
+use std::sync::LazyLock;
+
+use crate::cg_gallery::{CgGallery, CgView};
+use crate::compare::CompareMode;
+use crate::game::{HOVERBG, ListMode, Location, SceneMenu, VNToogleMsg, ViewRes, open_scene};
+use crate::input_map::{Action, ActionState, InputMap};
+use crate::spine_facade::{HasSpine, SkeletonAssets};
+use crate::thumbnail::{OffscreenSpine, SceneMenuScroll};
+
+// bundle paths carry the character ID as a number, e.g. r18_30005_01
+static CHARACTER_ID: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\d{4,}").unwrap());
+
+/// Search box query of the scene list.
+/// Words are fuzzy matched against bundle names, `c:<id>` filters by character ID and `v:<version>` by Spine version.
+#[derive(Resource, Default)]
+pub(crate) struct SceneFilter {
+    query: String,
+    // typing goes to the search box instead of hotkeys
+    pub(crate) focused: bool,
+    // keyboard selection among the matching entries
+    pub(crate) index: Option<usize>,
+    // Enter in the search box opens the first match
+    submit: bool,
+}
+
+impl SceneFilter {
+    // file is None for cg gallery labels
+    fn matches(&self, name: &str, file: Option<&Location>) -> bool {
+        self.query.split_whitespace().all(|word| {
+            if let Some(id) = word.strip_prefix("c:") {
+                character_id(name).or_else(|| file.and_then(|f| character_id(&f.path))).is_some_and(|c| c == id)
+            } else if let Some(version) = word.strip_prefix("v:") {
+                file.and_then(|f| f.version.as_deref()).is_some_and(|v| v.starts_with(version))
+            } else {
+                fuzzy_match(name, word)
+            }
+        })
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct SearchBox;
+
+#[derive(Component)]
+pub(crate) struct SearchText;
+
+pub(crate) struct SceneFilterPlugin;
+
+impl Plugin for SceneFilterPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SceneFilter>()
+            .add_systems(Update, (search_input, apply_scene_filter, scene_list_keys).chain());
+    }
+}
+
+fn character_id(text: &str) -> Option<&str> {
+    CHARACTER_ID.find(text).map(|m| m.as_str())
+}
+
+// every character of word appears in name in order, ignoring case
+fn fuzzy_match(name: &str, word: &str) -> bool {
+    let name = name.to_lowercase();
+    let mut chars = name.chars();
+    word.to_lowercase().chars().all(|c| chars.any(|n| n == c))
+}
+
+fn search_input(
+    mut keyboard: MessageReader<KeyboardInput>,
+    actions: Res<ActionState>,
+    search_query: Query<&Interaction, (Changed<Interaction>, With<SearchBox>)>,
+    mut filter: ResMut<SceneFilter>,
+    view_res: Res<ViewRes>,
+) {
+    if view_res.avg {
+        keyboard.clear();
+        return;
+    }
+    if search_query.iter().any(|i| *i == Interaction::Pressed)
+    || actions.just_pressed(Action::Search) {
+        filter.focused = true;
+        // the '/' itself is not typed
+        keyboard.clear();
+        return;
+    }
+    if !filter.focused {
+        keyboard.clear();
+        return;
+    }
+    // editing keys are not rebindable, actions are off while typing
+    for ev in keyboard.read() {
+        if !ev.state.is_pressed() {
+            continue;
+        }
+        match &ev.logical_key {
+            Key::Escape => {
+                if filter.query.is_empty() {
+                    filter.focused = false;
+                } else {
+                    filter.query.clear();
+                    filter.index = None;
+                }
+            }
+            Key::Enter => {
+                filter.focused = false;
+                filter.submit = true;
+            }
+            Key::Character(c) => {
+                filter.query.push_str(c);
+                filter.index = None;
+            }
+            Key::Space => {
+                filter.query.push(' ');
+            }
+            Key::Backspace => {
+                filter.query.pop();
+                filter.index = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn apply_scene_filter(
+    filter: Res<SceneFilter>,
+    added: Query<(), Added<SearchText>>,
+    input_map: Res<InputMap>,
+    mut item_query: Query<(&SceneMenu, &mut Node, &mut BackgroundColor)>,
+    mut search_text: Single<&mut Text, With<SearchText>>,
+    view_res: Res<ViewRes>,
+) {
+    if !filter.is_changed() && !input_map.is_changed() && added.is_empty() {
+        return;
+    }
+    // the placeholder lists the filter prefixes, the search key is rebindable
+    search_text.0 = if filter.query.is_empty() && !filter.focused {
+        format!("Search ({})  c:<id> v:<version>", input_map.describe(Action::Search))
+    } else {
+        format!("{}{}", filter.query, if filter.focused { "_" } else { "" })
+    };
+    let items = match view_res.mode {
+        ListMode::Memory => Some(&view_res.events),
+        ListMode::Cg => None,
+        _ => Some(&view_res.spines),
+    };
+    for (menu, mut node, mut bg_color) in item_query.iter_mut() {
+        let visible = match items {
+            Some(items) => items.get(&menu.0).is_some_and(|file| filter.matches(&menu.0, Some(file))),
+            None => filter.matches(&menu.0, None),
+        };
+        let display = if visible { Display::Flex } else { Display::None };
+        if node.display != display {
+            node.display = display;
+        }
+        if filter.index.is_none() {
+            *bg_color = Color::NONE.into();
+        }
+    }
+}
+
+fn scene_list_keys(
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    scroll: Single<(&Children, &mut ScrollPosition, &ComputedNode, &UiGlobalTransform), With<SceneMenuScroll>>,
+    mut item_query: Query<(&SceneMenu, &Node, &mut BackgroundColor, &ComputedNode, &UiGlobalTransform)>,
+    spine_query: Query<Entity, (HasSpine, Without<OffscreenSpine>)>,
+    mut skeletons: SkeletonAssets,
+    mut vn_ui_msg: MessageWriter<VNToogleMsg>,
+    mut filter: ResMut<SceneFilter>,
+    cg_view: Query<(), With<CgView>>,
+    cg: Res<CgGallery>,
+    compare: Res<CompareMode>,
+    mut view_res: ResMut<ViewRes>,
+) {
+    let submit = filter.submit;
+    if submit {
+        filter.submit = false;
+    }
+    // the list is hidden behind an open cg
+    if view_res.avg || !cg_view.is_empty() {
+        return;
+    }
+    let (children, mut scroll_pos, view, view_transform) = scroll.into_inner();
+    let visible: Vec<Entity> = children.iter()
+        .filter(|&child| item_query.get(child).is_ok_and(|(_, node, ..)| node.display != Display::None))
+        .collect();
+    if visible.is_empty() {
+        return;
+    }
+    let last = visible.len() - 1;
+    // the list is a 3 column grid
+    let step: Option<isize> = if actions.just_pressed(Action::Left) {
+        Some(-1)
+    } else if actions.just_pressed(Action::Right) {
+        Some(1)
+    } else if actions.just_pressed(Action::Up) {
+        Some(-3)
+    } else if actions.just_pressed(Action::Down) {
+        Some(3)
+    } else {
+        None
+    };
+    if let Some(step) = step {
+        let index = match filter.index {
+            Some(index) => index.saturating_add_signed(step).min(last),
+            None => 0,
+        };
+        filter.index = Some(index);
+        for (i, entity) in visible.iter().enumerate() {
+            let Ok((_, _, mut bg_color, node, transform)) = item_query.get_mut(*entity) else { continue };
+            *bg_color = if i == index { HOVERBG.into() } else { Color::NONE.into() };
+            if i != index {
+                continue;
+            }
+            // scroll the selected entry into view
+            let top = transform.translation.y - node.size().y / 2.;
+            let bottom = transform.translation.y + node.size().y / 2.;
+            let view_top = view_transform.translation.y - view.size().y / 2.;
+            let view_bottom = view_transform.translation.y + view.size().y / 2.;
+            if top < view_top {
+                scroll_pos.y -= (view_top - top) * view.inverse_scale_factor;
+            } else if bottom > view_bottom {
+                scroll_pos.y += (bottom - view_bottom) * view.inverse_scale_factor;
+            }
+        }
+    }
+    if submit || (actions.just_pressed(Action::Confirm) && filter.index.is_some()) {
+        let index = filter.index.unwrap_or(0).min(last);
+        if let Ok((menu, ..)) = item_query.get(visible[index]) {
+            let bundle_name = menu.0.clone();
+            open_scene(&bundle_name, &asset_server, &mut commands, &spine_query, &mut skeletons, &mut vn_ui_msg, &cg, &compare, &mut view_res);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_in_order() {
+        assert!(fuzzy_match("r18_30005_01", "r18"));
+        assert!(fuzzy_match("r18_30005_01", "3501"));
+        assert!(!fuzzy_match("r18_30005_01", "1503"));
+        assert!(!fuzzy_match("r18_30005_01", "r18_01_3"));
+    }
+
+    #[test]
+    fn fuzzy_ignores_case() {
+        assert!(fuzzy_match("Event_Beach", "evb"));
+        assert!(fuzzy_match("event_beach", "EVB"));
+    }
+
+    #[test]
+    fn fuzzy_empty_word() {
+        assert!(fuzzy_match("r18_30005_01", ""));
+        assert!(!fuzzy_match("", "a"));
+    }
+
+    #[test]
+    fn fuzzy_repeated_chars() {
+        assert!(fuzzy_match("r18_30005_01", "000"));
+        assert!(!fuzzy_match("r18_30005_01", "00000"));
+    }
+}
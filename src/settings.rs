@@ -0,0 +1,408 @@
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowMode, WindowResolution};
+use serde::{Deserialize, Serialize};
+use std::fs::read_to_string;
+use std::time::Duration;
+
+use crate::game::{AUTOFORWARD, FONT, HEADTEXT, HOVERBG, LISTTEXT, VNSPEED, VNText, ViewRes, Z_TEXT};
+use crate::input_map::{Action, ActionState};
+use crate::mixer::{AudioMixer, MixerChannel};
+use crate::utage4::{self, VNConfig};
+
+const SETTINGS_CONFIG: &str = "config.settings.json";
+// window sizes offered when not fullscreen
+const RESOLUTIONS: [[u32; 2]; 4] = [[1280, 720], [1920, 1080], [2560, 1440], [3840, 2160]];
+
+/// Player preferences, loaded before the window is created and saved to SETTINGS_CONFIG on change.
+/// Audio levels live in AudioMixer, the settings screen edits both.
+#[derive(Resource, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct UserSettings {
+    // milliseconds per character
+    text_speed: u64,
+    // milliseconds before auto mode advances a line without voice
+    auto_forward: u64,
+    pub(crate) fullscreen: bool,
+    // window size when not fullscreen
+    resolution: [u32; 2],
+    // multiplies the message window's own alpha
+    window_opacity: f32,
+    // one of utage4::LANGUAGES, None for the Text column
+    pub(crate) language: Option<String>,
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        Self {
+            text_speed: VNSPEED.as_millis() as u64,
+            auto_forward: AUTOFORWARD.as_millis() as u64,
+            fullscreen: true,
+            resolution: [3840, 2160],
+            window_opacity: 1.,
+            language: None,
+        }
+    }
+}
+
+impl UserSettings {
+    pub(crate) fn load() -> Self {
+        read_to_string(SETTINGS_CONFIG).ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn save(&self) {
+        match serde_json::to_string_pretty(self) {
+            Ok(content) => if let Err(e) = std::fs::write(SETTINGS_CONFIG, content) {
+                warn!("failed to save {}: {}", SETTINGS_CONFIG, e);
+            },
+            Err(e) => warn!("failed to serialize settings: {}", e),
+        }
+    }
+
+    fn text_speed(&self) -> Duration {
+        Duration::from_millis(self.text_speed)
+    }
+
+    pub(crate) fn auto_forward(&self) -> Duration {
+        Duration::from_millis(self.auto_forward)
+    }
+
+    pub(crate) fn window_mode(&self, launch: &LaunchWindow) -> WindowMode {
+        if launch.fullscreen(self) {
+            WindowMode::BorderlessFullscreen(MonitorSelection::Current)
+        } else {
+            WindowMode::Windowed
+        }
+    }
+
+    pub(crate) fn window_resolution(&self, launch: &LaunchWindow) -> WindowResolution {
+        let [width, height] = launch.resolution(self);
+        WindowResolution::new(width, height).with_scale_factor_override(1.)
+    }
+}
+
+/// `--windowed` and `--size` for this run, never saved.
+/// Each holds until the player changes the matching setting.
+#[derive(Resource, Default)]
+pub(crate) struct LaunchWindow {
+    pub(crate) windowed: bool,
+    pub(crate) size: Option<[u32; 2]>,
+}
+
+impl LaunchWindow {
+    pub(crate) fn fullscreen(&self, settings: &UserSettings) -> bool {
+        settings.fullscreen && !self.windowed
+    }
+
+    fn resolution(&self, settings: &UserSettings) -> [u32; 2] {
+        self.size.unwrap_or(settings.resolution)
+    }
+}
+
+/// The message window sprite and the alpha it was spawned with
+#[derive(Component)]
+pub(crate) struct MessageWindow(pub(crate) f32);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SettingItem {
+    TextSpeed,
+    AutoForward,
+    WindowMode,
+    Resolution,
+    Master,
+    Bgm,
+    Se,
+    Ambience,
+    Voice,
+    KeepVoice,
+    Character,
+    CharacterVolume,
+    CharacterMute,
+    WindowOpacity,
+    Language,
+}
+
+const SETTING_ITEMS: [SettingItem; 15] = [
+    SettingItem::TextSpeed,
+    SettingItem::AutoForward,
+    SettingItem::WindowMode,
+    SettingItem::Resolution,
+    SettingItem::Master,
+    SettingItem::Bgm,
+    SettingItem::Se,
+    SettingItem::Ambience,
+    SettingItem::Voice,
+    SettingItem::KeepVoice,
+    SettingItem::Character,
+    SettingItem::CharacterVolume,
+    SettingItem::CharacterMute,
+    SettingItem::WindowOpacity,
+    SettingItem::Language,
+];
+
+#[derive(Component)]
+struct SettingsPanel;
+
+/// Steps `item` down (-1) or up (1)
+#[derive(Component)]
+struct SettingButton {
+    item: SettingItem,
+    step: i32,
+}
+
+#[derive(Component)]
+struct SettingText(SettingItem);
+
+/// The character edited by the character voice rows, an index into character_names
+#[derive(Resource, Default)]
+struct VoiceCharacter(usize);
+
+// speakers as voices are tagged with them, sorted for a stable order
+fn character_names(vn: &VNConfig) -> Vec<&str> {
+    let mut names: Vec<&str> = vn.character.keys().map(String::as_str).collect();
+    names.sort_unstable();
+    names
+}
+
+pub(crate) struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VoiceCharacter>()
+            .add_systems(Update, (toggle_settings_screen, setting_buttons, setting_texts, apply_settings, save_settings).chain());
+    }
+}
+
+fn step_volume(channel: &mut MixerChannel, step: i32) {
+    channel.volume = (channel.volume + 0.1 * step as f32).clamp(0., 1.);
+}
+
+fn step_setting(
+    item: SettingItem,
+    step: i32,
+    settings: &mut UserSettings,
+    mixer: &mut AudioMixer,
+    character: &mut VoiceCharacter,
+    characters: &[&str],
+) {
+    match item {
+        SettingItem::TextSpeed => {
+            settings.text_speed = settings.text_speed.saturating_add_signed(10 * step as i64).clamp(10, 200);
+        }
+        SettingItem::AutoForward => {
+            settings.auto_forward = settings.auto_forward.saturating_add_signed(250 * step as i64).min(5000);
+        }
+        SettingItem::WindowMode => settings.fullscreen = !settings.fullscreen,
+        SettingItem::Resolution => {
+            let current = RESOLUTIONS.iter().position(|r| *r == settings.resolution).unwrap_or(RESOLUTIONS.len() - 1);
+            settings.resolution = RESOLUTIONS[(current as i32 + step).rem_euclid(RESOLUTIONS.len() as i32) as usize];
+        }
+        SettingItem::Master => step_volume(&mut mixer.master, step),
+        SettingItem::Bgm => step_volume(&mut mixer.bgm, step),
+        SettingItem::Se => step_volume(&mut mixer.se, step),
+        SettingItem::Ambience => step_volume(&mut mixer.ambience, step),
+        SettingItem::Voice => step_volume(&mut mixer.voice, step),
+        SettingItem::KeepVoice => mixer.keep_voice = !mixer.keep_voice,
+        SettingItem::Character => if !characters.is_empty() {
+            character.0 = (character.0 as i32 + step).rem_euclid(characters.len() as i32) as usize;
+        },
+        SettingItem::CharacterVolume => if let Some(name) = characters.get(character.0) {
+            step_volume(mixer.characters.entry(name.to_string()).or_default(), step);
+        },
+        SettingItem::CharacterMute => if let Some(name) = characters.get(character.0) {
+            let channel = mixer.characters.entry(name.to_string()).or_default();
+            channel.mute = !channel.mute;
+        },
+        SettingItem::WindowOpacity => {
+            settings.window_opacity = (settings.window_opacity + 0.1 * step as f32).clamp(0., 1.);
+        }
+        SettingItem::Language => {
+            // None comes before the first language
+            let languages: Vec<Option<&str>> = std::iter::once(None)
+                .chain(utage4::LANGUAGES.iter().map(|l| Some(*l)))
+                .collect();
+            let current = languages.iter().position(|l| *l == settings.language.as_deref()).unwrap_or(0);
+            let next = languages[(current as i32 + step).rem_euclid(languages.len() as i32) as usize];
+            settings.language = next.map(ToString::to_string);
+        }
+    }
+}
+
+fn setting_label(item: SettingItem, settings: &UserSettings, mixer: &AudioMixer, character: Option<&str>) -> String {
+    let channel = character.and_then(|name| mixer.characters.get(name)).copied().unwrap_or_default();
+    match item {
+        SettingItem::TextSpeed => format!("Text speed: {}ms per character", settings.text_speed),
+        SettingItem::AutoForward => format!("Auto delay: {:.2}s", settings.auto_forward as f32 / 1000.),
+        SettingItem::WindowMode => format!("Window: {}", if settings.fullscreen { "Fullscreen" } else { "Windowed" }),
+        SettingItem::Resolution => format!("Resolution: {}x{}", settings.resolution[0], settings.resolution[1]),
+        SettingItem::Master => format!("Master volume: {:.0}%", mixer.master.volume * 100.),
+        SettingItem::Bgm => format!("BGM volume: {:.0}%", mixer.bgm.volume * 100.),
+        SettingItem::Se => format!("SE volume: {:.0}%", mixer.se.volume * 100.),
+        SettingItem::Ambience => format!("Ambience volume: {:.0}%", mixer.ambience.volume * 100.),
+        SettingItem::Voice => format!("Voice volume: {:.0}%", mixer.voice.volume * 100.),
+        SettingItem::KeepVoice => format!("New line stops: {}", if mixer.keep_voice { "Same speaker" } else { "All voices" }),
+        SettingItem::Character => format!("Character: {}", character.unwrap_or("-")),
+        SettingItem::CharacterVolume => format!("  Voice volume: {:.0}%", channel.volume * 100.),
+        SettingItem::CharacterMute => format!("  Voice: {}", if channel.mute { "Muted" } else { "On" }),
+        SettingItem::WindowOpacity => format!("Message window opacity: {:.0}%", settings.window_opacity * 100.),
+        SettingItem::Language => format!("Language: {}", settings.language.as_deref().unwrap_or("Default")),
+    }
+}
+
+fn toggle_settings_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    actions: Res<ActionState>,
+    panel_query: Query<Entity, With<SettingsPanel>>,
+    view_res: Res<ViewRes>,
+) {
+    let open = !panel_query.is_empty();
+    if !actions.just_pressed(Action::Settings) && !(open && actions.just_pressed(Action::Exit)) {
+        return;
+    }
+    panel_query.iter().for_each(|entity| {
+        commands.entity(entity).despawn()
+    });
+    if open || view_res.avg {
+        return;
+    }
+    let font = |size: f32| TextFont {
+        font: asset_server.load(FONT).into(),
+        font_size: FontSize::Px(size),
+        ..default()
+    };
+    commands.spawn((
+        Visibility::Visible,
+        SettingsPanel,
+        ZIndex(Z_TEXT),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(40.),
+            left: Val::Percent(30.),
+            top: Val::Percent(5.),
+            padding: UiRect::all(px(20)),
+            row_gap: px(8),
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0., 0., 0., 0.9)),
+    )).with_children(|parent| {
+        parent.spawn((
+            Text::new("Settings"),
+            font(42.),
+            TextColor(HEADTEXT),
+        ));
+        for item in SETTING_ITEMS {
+            parent.spawn(Node {
+                column_gap: px(18),
+                align_items: AlignItems::Center,
+                ..default()
+            }).with_children(|parent| {
+                for (label, step) in [("<", -1), (">", 1)] {
+                    parent.spawn((
+                        Button,
+                        SettingButton {
+                            item,
+                            step,
+                        },
+                        Text::new(label),
+                        font(30.),
+                        TextColor(HEADTEXT),
+                        BackgroundColor(Color::NONE),
+                    ));
+                }
+                // text is filled in by setting_texts
+                parent.spawn((
+                    SettingText(item),
+                    Text::new(""),
+                    font(28.),
+                    TextColor(LISTTEXT),
+                ));
+            });
+        }
+    });
+}
+
+fn setting_buttons(
+    mut interaction_query: Query<(&Interaction, &SettingButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut settings: ResMut<UserSettings>,
+    mut mixer: ResMut<AudioMixer>,
+    mut character: ResMut<VoiceCharacter>,
+    view_res: Res<ViewRes>,
+) {
+    let characters = character_names(&view_res.vn);
+    interaction_query.iter_mut().for_each(|(interaction, button, mut bg_color)| {
+        match *interaction {
+            Interaction::Pressed => {
+                step_setting(button.item, button.step, &mut settings, &mut mixer, &mut character, &characters);
+            }
+            Interaction::Hovered => {
+                *bg_color = HOVERBG.into();
+            }
+            _ => {
+                *bg_color = Color::NONE.into();
+            }
+        }
+    });
+}
+
+fn setting_texts(
+    mut text_query: Query<(&mut Text, &SettingText)>,
+    added: Query<(), Added<SettingText>>,
+    settings: Res<UserSettings>,
+    mixer: Res<AudioMixer>,
+    character: Res<VoiceCharacter>,
+    view_res: Res<ViewRes>,
+) {
+    if !settings.is_changed() && !mixer.is_changed() && !character.is_changed() && added.is_empty() {
+        return;
+    }
+    let name = character_names(&view_res.vn).get(character.0).copied();
+    for (mut text, item) in text_query.iter_mut() {
+        text.0 = setting_label(item.0, &settings, &mixer, name);
+    }
+}
+
+// a new language shows from the next line
+fn apply_settings(
+    mut window: Single<&mut Window, With<PrimaryWindow>>,
+    mut vn_text: Single<&mut VNText>,
+    mut message_window: Query<(&mut Sprite, &MessageWindow)>,
+    mut launch_window: ResMut<LaunchWindow>,
+    // window settings of the last run, to tell when the player changes them
+    mut applied: Local<Option<(bool, [u32; 2])>>,
+    settings: Res<UserSettings>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    if let Some((fullscreen, resolution)) = *applied {
+        if fullscreen != settings.fullscreen {
+            launch_window.windowed = false;
+        }
+        if resolution != settings.resolution {
+            launch_window.size = None;
+        }
+    }
+    *applied = Some((settings.fullscreen, settings.resolution));
+    let mode = settings.window_mode(&launch_window);
+    if window.mode != mode {
+        window.mode = mode;
+    }
+    let [width, height] = launch_window.resolution(&settings);
+    if !launch_window.fullscreen(&settings)
+    && (window.resolution.physical_width() != width || window.resolution.physical_height() != height) {
+        window.resolution.set_physical_resolution(width, height);
+    }
+    vn_text.timer.set_duration(settings.text_speed());
+    for (mut sprite, alpha) in message_window.iter_mut() {
+        sprite.color.set_alpha(alpha.0 * settings.window_opacity);
+    }
+}
+
+fn save_settings(settings: Res<UserSettings>) {
+    if settings.is_changed() && !settings.is_added() {
+        settings.save();
+    }
+}
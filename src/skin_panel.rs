@@ -0,0 +1,274 @@
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use std::collections::BTreeMap;
+
+use crate::compare::ActiveViewer;
+use crate::game::{AnimeMenuList, FONT, HEADTEXT, HOVERBG, LISTTEXT, SELECTTEXT, Z_UI};
+use crate::spine_facade::{AnySpine, HasSpine, ViewerSpines, with_spine};
+
+// name of the skin combined from the selected skins
+const COMBINED_SKIN: &str = "viewer";
+// the runtime looks up attachments the current skin lacks in this one
+const DEFAULT_SKIN: &str = "default";
+
+/// Skins of the viewer spine picked in the skin panel, combined when more than one
+#[derive(Resource, Default)]
+pub(crate) struct SkinSelection {
+    skins: Vec<String>,
+    // shown while nothing is picked, the default skin or else the first one
+    base: Option<String>,
+}
+
+impl SkinSelection {
+    fn active(&self) -> Vec<&str> {
+        if self.skins.is_empty() {
+            self.base.iter().map(String::as_str).collect()
+        } else {
+            self.skins.iter().map(String::as_str).collect()
+        }
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct SkinPanel;
+
+#[derive(Component)]
+struct SkinButton(String);
+
+/// Cycles the slot through no attachment and every attachment the active skins put on it
+#[derive(Component)]
+struct SlotButton {
+    slot: String,
+    // (skin, attachment) of every skin with an attachment for the slot
+    attachments: Vec<(String, String)>,
+}
+
+pub(crate) struct SkinPanelPlugin;
+
+impl Plugin for SkinPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SkinSelection>()
+            .add_systems(Update, (choose_skin, toggle_attachment, skin_panel_scroll));
+    }
+}
+
+// tagged AnimeMenuList so it goes away with the animation list
+pub(crate) fn spawn_skin_panel(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    spines: &ViewerSpines,
+    entity: Entity,
+    selection: &mut SkinSelection,
+) {
+    let Ok(spine) = spines.query.get(entity) else { return };
+    // slots with the attachments of each skin on them and the current one
+    let Some((skins, slots)) = with_spine!(spine, |spine| {
+        let data = spine.skeleton.data();
+        let skins: Vec<String> = data.skins().map(|skin| skin.name().to_string()).collect();
+        let mut attachments: BTreeMap<usize, Vec<(String, String)>> = BTreeMap::new();
+        for skin in data.skins() {
+            for entry in skin.attachments() {
+                attachments.entry(entry.slot_index as usize).or_default()
+                    .push((skin.name().to_string(), entry.attachment.name().to_string()));
+            }
+        }
+        let slots: Vec<(String, Vec<(String, String)>, Option<String>)> = data.slots().enumerate()
+            .filter_map(|(i, slot)| attachments.remove(&i).map(|names| (slot.name().to_string(), names)))
+            .map(|(slot, names)| {
+                let current = spine.skeleton.find_slot(&slot)
+                    .and_then(|s| s.attachment().map(|a| a.name().to_string()));
+                (slot, names, current)
+            })
+            .collect();
+        (skins, slots)
+    }) else { return };
+    selection.skins.clear();
+    selection.base = skins.iter().find(|skin| *skin == DEFAULT_SKIN).or(skins.first()).cloned();
+    // skeletons with only the default skin and no props have nothing to choose
+    if skins.len() < 2 && slots.is_empty() {
+        return;
+    }
+    let head = TextFont {
+        font: asset_server.load(FONT).into(),
+        font_size: FontSize::Px(35.),
+        ..default()
+    };
+    let item = TextFont {
+        font: asset_server.load(FONT).into(),
+        font_size: FontSize::Px(26.),
+        ..default()
+    };
+    commands.spawn((
+        Visibility::Visible,
+        AnimeMenuList,
+        SkinPanel,
+        RelativeCursorPosition::default(),
+        ZIndex(Z_UI),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(18.),
+            height: Val::Percent(66.),
+            left: Val::Percent(13.),
+            top: Val::Percent(1.),
+            flex_direction: FlexDirection::Column,
+            overflow: Overflow::scroll_y(),
+            ..default()
+        },
+    )).with_children(|parent| {
+        parent.spawn((
+            Text::new("Skins"),
+            head.clone(),
+            TextColor(HEADTEXT),
+        ));
+        for skin in skins {
+            parent.spawn((
+                Button,
+                Text::new(skin.clone()),
+                SkinButton(skin),
+                item.clone(),
+                TextColor(LISTTEXT),
+                BackgroundColor(Color::NONE),
+            ));
+        }
+        parent.spawn((
+            Text::new("Slots"),
+            head,
+            TextColor(HEADTEXT),
+        ));
+        for (slot, attachments, current) in slots {
+            parent.spawn((
+                Button,
+                Text::new(slot_label(&slot, current.as_deref())),
+                SlotButton {
+                    slot,
+                    attachments,
+                },
+                item.clone(),
+                TextColor(LISTTEXT),
+                BackgroundColor(Color::NONE),
+            ));
+        }
+    });
+}
+
+fn slot_label(slot: &str, attachment: Option<&str>) -> String {
+    format!("{}: {}", slot, attachment.unwrap_or("-"))
+}
+
+fn choose_skin(
+    interaction_query: Query<(&Interaction, &SkinButton), Changed<Interaction>>,
+    mut skin_query: Query<(&Interaction, &SkinButton, &mut TextColor, &mut BackgroundColor)>,
+    mut slot_query: Query<(&SlotButton, &mut Text)>,
+    mut spine_query: Query<AnySpine, (HasSpine, With<ActiveViewer>)>,
+    mut selection: ResMut<SkinSelection>,
+) {
+    if interaction_query.is_empty() {
+        return;
+    }
+    let mut changed = false;
+    for (interaction, skin) in interaction_query.iter() {
+        if *interaction == Interaction::Pressed {
+            if let Some(i) = selection.skins.iter().position(|s| s == &skin.0) {
+                selection.skins.remove(i);
+            } else {
+                selection.skins.push(skin.0.clone());
+            }
+            changed = true;
+        }
+    }
+    for (interaction, skin, mut color, mut bg_color) in skin_query.iter_mut() {
+        let selected = selection.skins.contains(&skin.0);
+        *color = if selected || *interaction != Interaction::None { SELECTTEXT.into() } else { LISTTEXT.into() };
+        *bg_color = if selected { HOVERBG.into() } else { Color::NONE.into() };
+    }
+    if !changed {
+        return;
+    }
+    let active = selection.active();
+    for spine in spine_query.iter_mut() {
+        with_spine!(spine, |spine| {
+            let result = match active.as_slice() {
+                [] => Ok(()),
+                [skin] => spine.skeleton.set_skin_by_name(skin),
+                skins => spine.skeleton.set_skins_by_name(COMBINED_SKIN, skins.iter().copied()),
+            };
+            if let Err(e) = result {
+                warn!("failed to set skin: {}", e);
+            }
+            // attachments of the new skin only show from the setup pose
+            spine.skeleton.set_slots_to_setup_pose();
+            for (slot, mut text) in slot_query.iter_mut() {
+                let current = spine.skeleton.find_slot(&slot.slot)
+                    .and_then(|s| s.attachment().map(|a| a.name().to_string()));
+                text.0 = slot_label(&slot.slot, current.as_deref());
+            }
+        });
+    }
+}
+
+fn toggle_attachment(
+    mut interaction_query: Query<(
+        &Interaction,
+        &SlotButton,
+        &mut Text,
+        &mut TextColor,
+        &mut BackgroundColor,
+    ), (Changed<Interaction>, With<Button>)>,
+    mut spine_query: Query<AnySpine, (HasSpine, With<ActiveViewer>)>,
+    selection: Res<SkinSelection>,
+) {
+    let active = selection.active();
+    for (interaction, slot, mut text, mut color, mut bg_color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                let mut attachments: Vec<&String> = Vec::new();
+                for (skin, name) in slot.attachments.iter() {
+                    if (skin == DEFAULT_SKIN || active.contains(&skin.as_str())) && !attachments.contains(&name) {
+                        attachments.push(name);
+                    }
+                }
+                for spine in spine_query.iter_mut() {
+                    with_spine!(spine, |spine| {
+                        let current = spine.skeleton.find_slot(&slot.slot)
+                            .and_then(|s| s.attachment().map(|a| a.name().to_string()));
+                        // none -> first -> ... -> last -> none
+                        let next = match current.and_then(|c| attachments.iter().position(|a| **a == c)) {
+                            Some(i) => attachments.get(i + 1).copied(),
+                            None => attachments.first().copied(),
+                        };
+                        if !spine.skeleton.set_attachment(&slot.slot, next.map(String::as_str)) {
+                            warn!("attachment {:?} not in the current skin for slot {}", next, slot.slot);
+                        }
+                        text.0 = slot_label(&slot.slot, next.map(String::as_str));
+                    });
+                }
+            }
+            Interaction::Hovered => {
+                *color = SELECTTEXT.into();
+                *bg_color = HOVERBG.into();
+            }
+            _ => {
+                *color = LISTTEXT.into();
+                *bg_color = Color::NONE.into();
+            }
+        }
+    }
+}
+
+fn skin_panel_scroll(
+    mut panel_query: Query<(&mut ScrollPosition, &ComputedNode, &RelativeCursorPosition), With<SkinPanel>>,
+    mut scroll: MessageReader<MouseWheel>,
+) {
+    for ev in scroll.read() {
+        for (mut scroll_pos, node, cursor) in panel_query.iter_mut() {
+            if !cursor.cursor_over {
+                continue;
+            }
+            let visible_size = node.size() * node.inverse_scale_factor;
+            let content_size = node.content_size() * node.inverse_scale_factor;
+            let range = (content_size.y - visible_size.y).max(0.);
+            scroll_pos.y = (scroll_pos.y - ev.y * 40.).clamp(0., range);
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use bevy::app::ScheduleRunnerPlugin;
+use bevy::asset::RenderAssetUsages;
+use bevy::camera::RenderTarget;
+use bevy::camera::visibility::RenderLayers;
+use bevy::prelude::*;
+use bevy::render::gpu_readback::{Readback, ReadbackComplete};
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
+use bevy::window::ExitCondition;
+use bevy::winit::WinitPlugin;
+use bevy_auto_scaling::fixed_size_2d;
+use bevy_spine38::prelude as spine38;
+use bevy_spine42::prelude as spine42;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::camera::VIEW_SIZE;
+use crate::cli::assets_root;
+use crate::export::{ExportRequest, ExportSettings, unpad_rows, unpremultiply};
+use crate::game::{Location, load_spines};
+use crate::spine_facade::{AnySpine, HasSpine, SkeletonAssets, SkeletonReadyMsg, SkeletonSet, SpineFacadePlugin, UnlayeredSpineMeshes, step_spine, with_spine};
+use crate::thumbnail::OffscreenSpine;
+
+// render layer of skeletons being exported
+const EXPORT_LAYER: usize = 3;
+// frames between stepping the skeleton and reading the image back
+const EXPORT_FRAMES: u32 = 3;
+// frames to wait for a skeleton to load
+const EXPORT_TIMEOUT: u32 = 600;
+pub(crate) const EXPORT_SIZES: [(u32, u32); 4] = [(512, 512), (1024, 1024), (1920, 1080), (2048, 2048)];
+pub(crate) const EXPORT_FPS: [u32; 3] = [24, 30, 60];
+
+#[derive(Component)]
+#[require(OffscreenSpine, spine38::SpineManualUpdate, spine42::SpineManualUpdate)]
+struct ExportSpine;
+
+/// Bundle name of the skeleton shown in the viewer
+#[derive(Component)]
+pub(crate) struct ViewerBundle(pub(crate) String);
+
+pub(crate) struct ExportJob {
+    request: ExportRequest,
+    animation: String,
+    spine: Entity,
+    camera: Option<Entity>,
+    target: Handle<Image>,
+    pub(crate) frame: u32,
+    pub(crate) frame_count: u32,
+    wait: u32,
+    reading: bool,
+    frames: Vec<Vec<u8>>,
+}
+
+/// Animations are rendered one frame at a time to an offscreen image.
+/// The skeleton is stepped by 1/fps from the setup pose instead of the frame delta,
+/// so clips, 4.2 physics included, don't depend on the render frame rate.
+#[derive(Resource, Default)]
+pub(crate) struct ExportQueue {
+    pub(crate) pending: VecDeque<(ExportRequest, Location)>,
+    pub(crate) job: Option<ExportJob>,
+    // settings of exports started from the viewer
+    pub(crate) settings: ExportSettings,
+    // headless export quits after the queue runs empty
+    exit_when_done: bool,
+    // exports that produced no file, the headless export exits with an error status when any did
+    failed: u32,
+}
+
+pub(crate) struct SpineExportPlugin;
+
+impl Plugin for SpineExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ExportQueue>()
+            .add_systems(Update, (export_layers, render_export.in_set(SkeletonSet::OnReady)));
+    }
+}
+
+/// Exports without a window, the app exits when the file is written.
+/// The process exits with status 1 when the export fails.
+pub fn export(request: ExportRequest) {
+    let spines = load_spines();
+    let Some(file) = spines.get(&request.bundle).cloned() else {
+        eprintln!("bundle not found: {}", request.bundle);
+        std::process::exit(1);
+    };
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(
+                AssetPlugin {
+                    file_path: assets_root().to_string_lossy().into(),
+                    ..default()
+                }
+            ).set(
+                WindowPlugin {
+                    primary_window: None,
+                    exit_condition: ExitCondition::DontExit,
+                    ..default()
+                }
+            ).disable::<WinitPlugin>(),
+            ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(1. / 60.)),
+            SpineFacadePlugin,
+            SpineExportPlugin,
+        ))
+        .insert_resource(ExportQueue {
+            pending: VecDeque::from([(request, file)]),
+            exit_when_done: true,
+            ..default()
+        })
+        .run();
+}
+
+fn export_layers(
+    mut commands: Commands,
+    meshes: UnlayeredSpineMeshes,
+    export_spine_query: Query<(), With<ExportSpine>>,
+) {
+    for (entity, spine) in meshes.iter() {
+        if export_spine_query.contains(spine) {
+            commands.entity(entity).insert(RenderLayers::layer(EXPORT_LAYER));
+        }
+    }
+}
+
+fn render_export(
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+    mut spine_query: Query<AnySpine, (HasSpine, With<ExportSpine>)>,
+    mut skeleton_ready_msg: MessageReader<SkeletonReadyMsg>,
+    mut skeletons: SkeletonAssets,
+    mut images: ResMut<Assets<Image>>,
+    mut queue: ResMut<ExportQueue>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    if queue.job.is_none() {
+        let Some((request, file)) = queue.pending.pop_front() else {
+            if queue.exit_when_done {
+                exit.write(if queue.failed > 0 { AppExit::error() } else { AppExit::Success });
+            }
+            return;
+        };
+        let mut spine = commands.spawn((
+            ExportSpine,
+            Transform::default(),
+        ));
+        skeletons.insert(&mut spine, &asset_server, &file);
+        let spine = spine.id();
+        let mut target = Image::new_fill(
+            Extent3d {
+                width: request.settings.width,
+                height: request.settings.height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 0],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        target.texture_descriptor.usage |=
+            TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC | TextureUsages::TEXTURE_BINDING;
+        queue.job = Some(ExportJob {
+            request,
+            animation: String::new(),
+            spine,
+            camera: None,
+            target: images.add(target),
+            frame: 0,
+            frame_count: 0,
+            wait: 0,
+            reading: false,
+            frames: vec!(),
+        });
+    }
+    let Some(job) = queue.job.as_mut() else { return };
+
+    let mut failed = false;
+    for msg in skeleton_ready_msg.read() {
+        if msg.entity != job.spine {
+            continue;
+        }
+        let Ok(mut spine) = spine_query.get_mut(msg.entity) else { continue };
+        let Some((animation, x, y, width, height)) = with_spine!(&mut spine, |spine| {
+            let (animation, x, y, width, height) = {
+                let data = spine.skeleton.data();
+                let animation = data.animations()
+                    .find(|a| job.request.animation.as_ref().is_none_or(|name| a.name() == name.as_str()))
+                    .map(|a| (a.name().to_string(), a.duration()));
+                (animation, data.x(), data.y(), data.width(), data.height())
+            };
+            spine.skeleton.set_to_setup_pose();
+            if let Some((animation, _)) = &animation {
+                let _ = spine.animation_state.set_animation_by_name(0, animation, true);
+            }
+            (animation, x, y, width, height)
+        }) else { continue };
+        // first frame
+        step_spine!(&mut spine, 0.);
+        let Some((animation, duration)) = animation else {
+            warn!("animation not found: {} {:?}", job.request.bundle, job.request.animation);
+            failed = true;
+            break;
+        };
+        let ExportSettings { width: target_width, height: target_height, fps, .. } = job.request.settings;
+        job.animation = animation;
+        job.frame_count = ((duration * fps as f32).round() as u32).max(1);
+        // fit the setup pose bounds into the target aspect
+        let aspect = target_width as f32 / target_height as f32;
+        let (center, size) = if width > 0. && height > 0. {
+            let size = if width / height > aspect { vec2(width, width / aspect) } else { vec2(height * aspect, height) };
+            (vec2(x + width / 2., y + height / 2.), size * 1.05)
+        } else {
+            (Vec2::ZERO, vec2(VIEW_SIZE.y * aspect, VIEW_SIZE.y))
+        };
+        job.camera = Some(commands.spawn((
+            Camera2d,
+            Camera {
+                order: -2,
+                clear_color: ClearColorConfig::Custom(Color::NONE),
+                ..default()
+            },
+            RenderTarget::Image(job.target.clone().into()),
+            RenderLayers::layer(EXPORT_LAYER),
+            fixed_size_2d(size.x, size.y),
+            Transform::from_translation(center.extend(0.)),
+        )).id());
+    }
+
+    let timed_out = job.camera.is_none() && job.wait > EXPORT_TIMEOUT;
+    if failed || timed_out {
+        if timed_out {
+            warn!("export skeleton not ready: {}", job.request.bundle);
+        }
+        commands.entity(job.spine).despawn();
+        queue.job = None;
+        queue.failed += 1;
+        return;
+    }
+    if job.camera.is_none() {
+        job.wait += 1;
+    } else if !job.reading {
+        if job.wait == 0 && job.frame > 0 && let Ok(spine) = spine_query.get_mut(job.spine) {
+            step_spine!(spine, 1. / job.request.settings.fps as f32);
+        }
+        job.wait += 1;
+        if job.wait >= EXPORT_FRAMES {
+            job.reading = true;
+            commands.spawn(Readback::texture(job.target.clone())).observe(export_readback);
+        }
+    }
+}
+
+fn export_readback(
+    readback: On<ReadbackComplete>,
+    mut commands: Commands,
+    mut queue: ResMut<ExportQueue>,
+) {
+    commands.entity(readback.entity).despawn();
+    let Some(job) = queue.job.as_mut() else { return };
+    if !job.reading {
+        return;
+    }
+    let ExportSettings { width, height, .. } = job.request.settings;
+    let mut frame = unpad_rows(&readback.data, width, height);
+    unpremultiply(&mut frame);
+    job.frames.push(frame);
+    job.frame += 1;
+    job.wait = 0;
+    job.reading = false;
+    if job.frame < job.frame_count {
+        return;
+    }
+    let Some(job) = queue.job.take() else { return };
+    match job.request.write(&job.animation, job.frames) {
+        Ok(path) => info!("exported {}", path.display()),
+        Err(e) => {
+            warn!("failed to export {} {}: {}", job.request.bundle, job.animation, e);
+            queue.failed += 1;
+        }
+    }
+    commands.entity(job.spine).despawn();
+    if let Some(camera) = job.camera {
+        commands.entity(camera).despawn();
+    }
+}
@@ -0,0 +1,289 @@
+use bevy::camera::visibility::RenderLayers;
+use bevy::ecs::query::QueryData;
+use bevy::ecs::system::{EntityCommands, SystemParam};
+use bevy::prelude::*;
+use bevy_spine38::prelude as spine38;
+use bevy_spine42::prelude as spine42;
+
+use crate::anime_control::MAX_TRACK;
+use crate::game::Location;
+use crate::profile::{SpineRuntime, profile};
+use crate::spine_export::ViewerBundle;
+
+/// Entities with a skeleton of either runtime
+pub(crate) type HasSpine = Or<(With<spine38::Spine>, With<spine42::Spine>)>;
+
+/// The Spine component of an entity, whichever runtime it runs on.
+/// Matches entities without a skeleton too, filter with HasSpine where that matters.
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub(crate) struct AnySpine {
+    pub(crate) v38: Option<&'static mut spine38::Spine>,
+    pub(crate) v42: Option<&'static mut spine42::Spine>,
+}
+
+// rusty_spine 3.8 and 4.2 share their API, so `$body` is compiled once for each Spine type.
+// `$runtime` names the prelude of the runtime for the few types the body spells out.
+macro_rules! with_spine {
+    ($item:expr, |$spine:ident| $body:expr) => {
+        $crate::spine_facade::with_spine!($item, |$spine, _runtime| $body)
+    };
+    ($item:expr, |$spine:ident, $runtime:ident| $body:expr) => {{
+        #[allow(unused_mut)]
+        let mut item = $item;
+        if let Some($spine) = &mut item.v38 {
+            #[allow(unused_imports)]
+            use bevy_spine38::prelude as $runtime;
+            Some($body)
+        } else if let Some($spine) = &mut item.v42 {
+            #[allow(unused_imports)]
+            use bevy_spine42::prelude as $runtime;
+            Some($body)
+        } else {
+            None
+        }
+    }};
+}
+pub(crate) use with_spine;
+
+// advances a skeleton by `$delta` seconds, SkeletonController::update of 4.2 also steps physics
+macro_rules! step_spine {
+    ($item:expr, $delta:expr) => {{
+        #[allow(unused_mut)]
+        let mut item = $item;
+        let delta: f32 = $delta;
+        if let Some(spine) = &mut item.v38 {
+            spine.update(delta);
+        } else if let Some(spine) = &mut item.v42 {
+            spine.update(delta, bevy_spine42::rusty_spine::Physics::Update);
+        }
+    }};
+}
+pub(crate) use step_spine;
+
+// SkeletonData of `$runtime` for a skeleton file and the atlas next to it
+macro_rules! skeleton_data {
+    ($runtime:ident, $asset_server:expr, $file:expr) => {{
+        let (asset_server, file): (&AssetServer, &Location) = ($asset_server, $file);
+        let atlas = asset_server.load(format!("{}/{}.atlas", file.path, file.name));
+        let skeleton = format!("{}/{}.{}", file.path, file.name, file.ext);
+        if file.ext == "skel" {
+            $runtime::SkeletonData::new_from_binary(asset_server.load(skeleton), atlas)
+        } else {
+            $runtime::SkeletonData::new_from_json(asset_server.load(skeleton), atlas)
+        }
+    }};
+}
+
+/// A skeleton of either runtime is ready
+#[derive(Message)]
+pub(crate) struct SkeletonReadyMsg(pub(crate) Entity);
+
+/// SpineMsg of either runtime. Event audio is played by the SpineAudioPlugin of each runtime.
+#[derive(Message)]
+pub(crate) enum SkeletonMsg {
+    Start { entity: Entity, animation: String },
+    Interrupt { entity: Entity, animation: String },
+    End { entity: Entity, animation: String },
+    Complete { entity: Entity, animation: String },
+    Dispose { entity: Entity },
+    Event { entity: Entity, name: String, int: i32, float: f32, string: String },
+}
+
+impl SkeletonMsg {
+    pub(crate) fn entity(&self) -> Entity {
+        match self {
+            SkeletonMsg::Start { entity, .. }
+            | SkeletonMsg::Interrupt { entity, .. }
+            | SkeletonMsg::End { entity, .. }
+            | SkeletonMsg::Complete { entity, .. }
+            | SkeletonMsg::Dispose { entity }
+            | SkeletonMsg::Event { entity, .. } => *entity,
+        }
+    }
+}
+
+/// Systems reading SkeletonReadyMsg or SkeletonMsg, after the messages of both runtimes are forwarded
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum SkeletonSet {
+    OnReady,
+    OnEvent,
+}
+
+/// Runs the Spine 3.8 and 4.2 runtimes side by side, each skeleton is loaded by the runtime of its version
+pub(crate) struct SpineFacadePlugin;
+
+impl Plugin for SpineFacadePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((spine38::SpinePlugin, spine42::SpinePlugin))
+            .add_message::<SkeletonReadyMsg>()
+            .add_message::<SkeletonMsg>()
+            .configure_sets(Update, (
+                SkeletonSet::OnReady.after(spine38::SpineSet::OnReady).after(spine42::SpineSet::OnReady),
+                SkeletonSet::OnEvent.after(spine38::SpineSet::OnEvent).after(spine42::SpineSet::OnEvent),
+            ))
+            .add_systems(Update, (
+                forward_ready38.in_set(spine38::SpineSet::OnReady),
+                forward_ready42.in_set(spine42::SpineSet::OnReady),
+                forward_msg38.in_set(spine38::SpineSet::OnEvent),
+                forward_msg42.in_set(spine42::SpineSet::OnEvent),
+            ));
+    }
+}
+
+/// The runtime for a skeleton from the version in its header, the profile's runtime when unknown
+fn runtime_of(file: &Location) -> SpineRuntime {
+    match file.version.as_deref() {
+        Some(version) if version.starts_with("3.") => SpineRuntime::V38,
+        Some(version) if version.starts_with("4.") => SpineRuntime::V42,
+        _ => profile().spine,
+    }
+}
+
+/// Skeleton assets of both runtimes
+#[derive(SystemParam)]
+pub(crate) struct SkeletonAssets<'w> {
+    v38: ResMut<'w, Assets<spine38::SkeletonData>>,
+    v42: ResMut<'w, Assets<spine42::SkeletonData>>,
+}
+
+impl SkeletonAssets<'_> {
+    /// Inserts the SkeletonDataHandle of the runtime matching `file`
+    pub(crate) fn insert(&mut self, entity: &mut EntityCommands, asset_server: &AssetServer, file: &Location) {
+        match runtime_of(file) {
+            SpineRuntime::V38 => {
+                entity.insert(spine38::SkeletonDataHandle(self.v38.add(skeleton_data!(spine38, asset_server, file))));
+            }
+            SpineRuntime::V42 => {
+                entity.insert(spine42::SkeletonDataHandle(self.v42.add(skeleton_data!(spine42, asset_server, file))));
+            }
+        }
+    }
+}
+
+/// Skeleton meshes of both runtimes not put on a render layer yet
+#[derive(SystemParam)]
+pub(crate) struct UnlayeredSpineMeshes<'w, 's> {
+    v38: Query<'w, 's, (Entity, &'static spine38::SpineMesh), Without<RenderLayers>>,
+    v42: Query<'w, 's, (Entity, &'static spine42::SpineMesh), Without<RenderLayers>>,
+}
+
+impl UnlayeredSpineMeshes<'_, '_> {
+    /// Mesh entities with the skeleton entity they belong to
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (Entity, Entity)> + '_ {
+        self.v38.iter().map(|(entity, mesh)| (entity, mesh.spine_entity))
+            .chain(self.v42.iter().map(|(entity, mesh)| (entity, mesh.spine_entity)))
+    }
+}
+
+/// Event audio settings of both runtimes
+#[derive(SystemParam)]
+pub(crate) struct SpineAudio<'w> {
+    v38: ResMut<'w, spine38::SpineAudioSettings>,
+    v42: ResMut<'w, spine42::SpineAudioSettings>,
+}
+
+impl SpineAudio<'_> {
+    pub(crate) fn set_volume(&mut self, volume: f32) {
+        self.v38.volume = volume;
+        self.v42.volume = volume;
+    }
+}
+
+/// Viewer skeletons of both runtimes behind one API
+#[derive(SystemParam)]
+pub(crate) struct ViewerSpines<'w, 's> {
+    pub(crate) query: Query<'w, 's, AnySpine, (HasSpine, With<ViewerBundle>)>,
+}
+
+impl ViewerSpines<'_, '_> {
+    pub(crate) fn animations(&mut self, entity: Entity) -> Vec<String> {
+        self.query.get_mut(entity).ok()
+            .and_then(|spine| with_spine!(spine, |spine| spine.animation_state.data().skeleton_data().animations()
+                .map(|animation| animation.name().to_string())
+                .collect()))
+            .unwrap_or_default()
+    }
+
+    // setup pose bounds
+    pub(crate) fn bounds(&mut self, entity: Entity) -> Option<Rect> {
+        self.query.get_mut(entity).ok().and_then(|spine| with_spine!(spine, |spine| {
+            let data = spine.skeleton.data();
+            Rect::new(data.x(), data.y(), data.x() + data.width(), data.y() + data.height())
+        }))
+    }
+
+    pub(crate) fn set_timescale(&mut self, entity: Entity, timescale: f32) {
+        if let Ok(spine) = self.query.get_mut(entity) {
+            with_spine!(spine, |spine| spine.animation_state.set_timescale(timescale));
+        }
+    }
+
+    /// false when the skeleton has no such animation
+    pub(crate) fn set_animation(&mut self, entity: Entity, track: usize, name: &str, looping: bool, mix: f32) -> bool {
+        self.query.get_mut(entity).ok()
+            .and_then(|spine| with_spine!(spine, |spine| match spine.animation_state.set_animation_by_name(track, name, looping) {
+                Ok(mut entry) => {
+                    entry.set_mix_duration(mix);
+                    true
+                }
+                Err(_) => false,
+            }))
+            .unwrap_or(false)
+    }
+
+    /// Track times of tracks 0..=MAX_TRACK, None for empty tracks
+    pub(crate) fn track_times(&mut self, entity: Entity) -> Vec<Option<f32>> {
+        self.query.get_mut(entity).ok()
+            .and_then(|spine| with_spine!(spine, |spine| (0..=MAX_TRACK)
+                .map(|track| spine.animation_state.get_current(track).map(|entry| entry.track_time()))
+                .collect()))
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn set_track_times(&mut self, entity: Entity, times: &[Option<f32>]) {
+        let Ok(spine) = self.query.get_mut(entity) else { return };
+        with_spine!(spine, |spine| {
+            for (track, time) in times.iter().enumerate() {
+                if let Some(time) = time && let Some(mut entry) = spine.animation_state.track_at_index_mut(track) {
+                    entry.set_track_time(*time);
+                }
+            }
+        });
+    }
+}
+
+// the message types of both runtimes match field for field
+macro_rules! forward_runtime_msgs {
+    ($forward_ready:ident, $forward_msg:ident, $runtime:ident) => {
+        fn $forward_ready(
+            mut spine_ready_msg: MessageReader<$runtime::SpineReadyMsg>,
+            mut skeleton_ready_msg: MessageWriter<SkeletonReadyMsg>,
+        ) {
+            for msg in spine_ready_msg.read() {
+                skeleton_ready_msg.write(SkeletonReadyMsg(msg.entity));
+            }
+        }
+
+        fn $forward_msg(
+            mut spine_msg: MessageReader<$runtime::SpineMsg>,
+            mut skeleton_msg: MessageWriter<SkeletonMsg>,
+        ) {
+            for msg in spine_msg.read() {
+                skeleton_msg.write(match msg.clone() {
+                    $runtime::SpineMsg::Start { entity, animation } => SkeletonMsg::Start { entity, animation },
+                    $runtime::SpineMsg::Interrupt { entity, animation } => SkeletonMsg::Interrupt { entity, animation },
+                    $runtime::SpineMsg::End { entity, animation } => SkeletonMsg::End { entity, animation },
+                    $runtime::SpineMsg::Complete { entity, animation } => SkeletonMsg::Complete { entity, animation },
+                    $runtime::SpineMsg::Dispose { entity } => SkeletonMsg::Dispose { entity },
+                    $runtime::SpineMsg::Event { entity, name, int, float, string, .. } => {
+                        SkeletonMsg::Event { entity, name, int, float, string }
+                    }
+                });
+            }
+        }
+    };
+}
+
+forward_runtime_msgs!(forward_ready38, forward_msg38, spine38);
+forward_runtime_msgs!(forward_ready42, forward_msg42, spine42);
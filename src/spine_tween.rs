@@ -1,15 +1,32 @@
-pub struct SpineColorLens {
-    pub start: Color,
-    pub end: Color,
-}
+use bevy::prelude::*;
+use bevy_spine38::prelude as spine38;
+use bevy_spine42::prelude as spine42;
+use bevy_tweening::Lens;
+use std::time::Duration;
 
-impl Lens<Spine> for SpineColorLens {
-    fn lerp(&mut self, mut target: Mut<Spine>, ratio: f32) {
-        let rgba = self.start.mix(&self.end, ratio).to_linear();
-        target.skeleton.set_color(rgba.red, rgba.green, rgba.blue, rgba.alpha);
-    }
+use crate::game::WaitEffect;
+use crate::tween::TweenType;
+
+// one lens per runtime, a tween takes its component type from the lens
+macro_rules! spine_color_lens {
+    ($lens:ident, $runtime:ident) => {
+        pub struct $lens {
+            pub start: Color,
+            pub end: Color,
+        }
+
+        impl Lens<$runtime::Spine> for $lens {
+            fn lerp(&mut self, mut target: Mut<$runtime::Spine>, ratio: f32) {
+                let rgba = self.start.mix(&self.end, ratio).to_linear();
+                target.skeleton.set_color(rgba.red, rgba.green, rgba.blue, rgba.alpha);
+            }
+        }
+    };
 }
 
+spine_color_lens!(SpineColorLens38, spine38);
+spine_color_lens!(SpineColorLens42, spine42);
+
 #[derive(PartialEq, Clone, Copy)]
 enum ShakeKind {
     Punch,
@@ -24,7 +41,7 @@ enum ShakeAxes {
 }
 
 #[derive(Component)]
-struct ShakeAnim {
+pub(crate) struct ShakeAnim {
     kind: ShakeKind,
     axes: ShakeAxes,
     amp: Vec3,
@@ -56,7 +73,7 @@ fn shake_kind_axes(tween_type: &TweenType) -> (ShakeKind, ShakeAxes) {
     (kind, axes)
 }
 
-fn spawn_shake(
+pub(crate) fn spawn_shake(
     commands: &mut Commands,
     entity: Entity,
     tween_type: &TweenType,
@@ -89,7 +106,7 @@ fn rand_unit(seed: &mut u32) -> f32 {
     ((*seed >> 8) as f32 / 16777216.) * 2. - 1.
 }
 
-fn shake_anim(
+pub(crate) fn shake_anim(
     mut commands: Commands,
     mut query: Query<(Entity, &mut ShakeAnim, &mut Transform)>,
     time: Res<Time>,
@@ -0,0 +1,138 @@
+use serde::Deserialize;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+// head of the file read for the version, the json "skeleton" object and the binary header fit in it
+const HEAD_SIZE: u64 = 4096;
+
+/// The "skeleton" object leading a json export
+#[derive(Deserialize)]
+struct SkeletonHeader {
+    spine: Option<String>,
+}
+
+/// Reads the Spine editor version ("3.8", "4.2", ...) from the header of a skeleton file
+pub fn detect(path: &Path) -> Option<String> {
+    let mut head = Vec::new();
+    File::open(path)
+        .ok()?
+        .take(HEAD_SIZE)
+        .read_to_end(&mut head)
+        .ok()?;
+    if path.extension().is_some_and(|ext| ext == "skel") {
+        binary_version(&head)
+    } else {
+        json_version(&head)
+    }
+}
+
+// only the "skeleton" value is deserialized, the head usually cuts the rest of the file short
+fn json_version(head: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(head);
+    let key = text.find("\"skeleton\"")?;
+    let start = key + text[key..].find('{')?;
+    let mut deserializer = serde_json::Deserializer::from_str(&text[start..]);
+    let header = SkeletonHeader::deserialize(&mut deserializer).ok()?;
+    major_minor(&header.spine?)
+}
+
+// 3.8 binaries start with the hash string, 4.x ones with an 8 byte hash, the version string follows
+fn binary_version(head: &[u8]) -> Option<String> {
+    let mut input = head;
+    read_string(&mut input)
+        .and_then(|_| read_string(&mut input))
+        .and_then(|version| major_minor(&version))
+        .or_else(|| read_string(&mut head.get(8..)?).and_then(|version| major_minor(&version)))
+}
+
+// varint byte count plus one, then UTF-8, the same as readString of the runtimes; a null string reads as ""
+fn read_string(input: &mut &[u8]) -> Option<String> {
+    let mut length = 0;
+    for shift in (0..35).step_by(7) {
+        let (&byte, rest) = input.split_first()?;
+        *input = rest;
+        length |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    let length = length.saturating_sub(1);
+    if input.len() < length {
+        return None;
+    }
+    let (bytes, rest) = input.split_at(length);
+    *input = rest;
+    String::from_utf8(bytes.to_vec()).ok()
+}
+
+// "3.8.99" to "3.8"
+fn major_minor(version: &str) -> Option<String> {
+    let mut parts = version.split('.');
+    let (major, minor) = (parts.next()?, parts.next()?);
+    (major.parse::<u32>().is_ok() && minor.parse::<u32>().is_ok())
+        .then(|| format!("{}.{}", major, minor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(s: &str) -> Vec<u8> {
+        std::iter::once(s.len() as u8 + 1)
+            .chain(s.bytes())
+            .collect()
+    }
+
+    #[test]
+    fn json_38() {
+        let head = br#"{
+"skeleton": { "hash": "k7vLbBE0y0c", "spine": "3.8.99", "x": -120.5, "y": -8, "width": 241, "height": 540, "images": "./images/" },
+"bones": [
+	{ "name": "root" },
+	{ "name": "body", "parent": "ro"#;
+        assert_eq!(json_version(head).as_deref(), Some("3.8"));
+    }
+
+    #[test]
+    fn json_42() {
+        let head = br#"{"skeleton":{"hash":"Qm2Hcm5+3aY","spine":"4.2.43","x":-200,"y":-10,"width":400,"height":720},"bones":[{"name":"root"},{"na"#;
+        assert_eq!(json_version(head).as_deref(), Some("4.2"));
+    }
+
+    #[test]
+    fn json_without_version() {
+        assert_eq!(
+            json_version(br#"{"skeleton":{"hash":"x"},"bones":[]}"#),
+            None
+        );
+        assert_eq!(json_version(br#"{"bones":[]}"#), None);
+    }
+
+    #[test]
+    fn binary_38() {
+        let mut head = [string("k7vLbBE0y0c"), string("3.8.99")].concat();
+        // nonessential data and the first bone
+        head.extend([0x42, 0xf1, 0x00, 0x00, 0x01, 0x05, b'r', b'o', b'o', b't']);
+        assert_eq!(binary_version(&head).as_deref(), Some("3.8"));
+        // a null hash
+        assert_eq!(
+            binary_version(&[vec![0], string("3.8.95")].concat()).as_deref(),
+            Some("3.8")
+        );
+    }
+
+    #[test]
+    fn binary_42() {
+        let mut head = vec![0x9a, 0x3e, 0x71, 0x0c, 0xd2, 0x05, 0x8f, 0x44];
+        head.extend(string("4.2.43"));
+        head.extend([0xc2, 0xf0, 0x00, 0x00]);
+        assert_eq!(binary_version(&head).as_deref(), Some("4.2"));
+    }
+
+    #[test]
+    fn binary_without_version() {
+        assert_eq!(binary_version(&[]), None);
+        assert_eq!(binary_version(&[0xff; 16]), None);
+    }
+}
@@ -0,0 +1,291 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::camera::RenderTarget;
+use bevy::camera::visibility::RenderLayers;
+use bevy::prelude::*;
+use bevy::render::gpu_readback::{Readback, ReadbackComplete};
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
+use bevy::ui::UiGlobalTransform;
+use bevy_auto_scaling::fixed_size_2d;
+use std::collections::VecDeque;
+use std::fs::read_to_string;
+
+use crate::camera::VIEW_SIZE;
+use crate::cg_gallery::{CgGallery, cg_thumbnail};
+use crate::cli::asset_path;
+use crate::export::unpremultiply;
+use crate::game::{BG, EVENT, ListMode, Location, SPRITE, ViewRes, str};
+use crate::spine_facade::{AnySpine, HasSpine, SkeletonAssets, SkeletonReadyMsg, SkeletonSet, UnlayeredSpineMeshes, with_spine};
+use crate::utage4;
+
+// render layer of skeletons drawn for thumbnails, never seen by the scene camera
+const THUMB_LAYER: usize = 2;
+// multiple of 64 so readback rows need no padding
+const THUMB_SIZE: u32 = 384;
+// under assets so cached thumbnails load through the asset server
+const THUMB_CACHE: &str = "cache/thumbnail/";
+// frames to wait after the camera spawns before reading the image back
+const THUMB_FRAMES: u32 = 3;
+// frames to wait for a skeleton to load, and for the readback of its image
+const THUMB_TIMEOUT: u32 = 600;
+
+/// Thumbnail of a SceneMenu entry, requested once it scrolls near the view
+#[derive(Component)]
+pub(crate) struct SceneThumb {
+    pub(crate) name: String,
+    pub(crate) requested: bool,
+}
+
+#[derive(Component)]
+pub(crate) struct SceneMenuScroll;
+
+/// Skeletons rendered to an image instead of the view, the viewer and VN queries skip them
+#[derive(Component, Default)]
+pub(crate) struct OffscreenSpine;
+
+#[derive(Component)]
+#[require(OffscreenSpine)]
+struct ThumbnailSpine;
+
+struct ThumbnailJob {
+    name: String,
+    thumb: Entity,
+    spine: Entity,
+    camera: Option<Entity>,
+    target: Handle<Image>,
+    frames: u32,
+    readback: Option<Entity>,
+}
+
+/// Skeletons are rendered one at a time to an offscreen image, then saved to THUMB_CACHE
+#[derive(Resource, Default)]
+struct ThumbnailQueue {
+    pending: VecDeque<(String, Entity)>,
+    job: Option<ThumbnailJob>,
+}
+
+pub(crate) struct ThumbnailPlugin;
+
+impl Plugin for ThumbnailPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ThumbnailQueue>()
+            .add_systems(Update, (
+                request_thumbnails,
+                thumbnail_layers,
+                render_thumbnails.in_set(SkeletonSet::OnReady),
+            ));
+    }
+}
+
+// background of the first Bg command in a memory book
+fn memory_thumbnail(file: &Location, view_res: &ViewRes) -> Option<String> {
+    let content = read_to_string(asset_path(format!("{}/{}.{}", file.path, file.name, file.ext))).ok()?;
+    let book = utage4::parse_book(content);
+    let node = book.iter().find(|n| n.command.as_deref() == Some("Bg"))?;
+    let texture = view_res.vn.texture.get(str!(node.arg1))?;
+    let img_path = match texture.entry_type.as_deref() {
+        Some("Event") => EVENT.as_str(),
+        Some("Sprite") => SPRITE.as_str(),
+        _ => BG.as_str(),
+    };
+    Some(format!("{}{}", img_path, texture.file_name.as_deref()?))
+}
+
+fn request_thumbnails(
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+    scroll: Single<(&ComputedNode, &UiGlobalTransform), With<SceneMenuScroll>>,
+    mut thumb_query: Query<(Entity, &mut SceneThumb, &ComputedNode, &UiGlobalTransform)>,
+    mut queue: ResMut<ThumbnailQueue>,
+    cg: Res<CgGallery>,
+    view_res: Res<ViewRes>,
+) {
+    let (view, view_transform) = *scroll;
+    // preload one screen above and below the visible part of the list
+    let range = view.size().y * 1.5;
+    for (entity, mut thumb, node, transform) in thumb_query.iter_mut() {
+        // entries hidden by the scene filter have no size
+        if thumb.requested || node.size() == Vec2::ZERO || (transform.translation.y - view_transform.translation.y).abs() > range + node.size().y {
+            continue;
+        }
+        thumb.requested = true;
+        if view_res.mode == ListMode::Memory {
+            if let Some(path) = view_res.events.get(&thumb.name).and_then(|file| memory_thumbnail(file, &view_res)) {
+                commands.entity(entity).insert(ImageNode::new(asset_server.load(path)));
+            }
+            continue;
+        }
+        if view_res.mode == ListMode::Cg {
+            // locked entries stay blank
+            if cg.unlocked.contains(&thumb.name) && let Some(path) = cg_thumbnail(&thumb.name, &view_res) {
+                commands.entity(entity).insert(ImageNode::new(asset_server.load(path)));
+            }
+            continue;
+        }
+        let cache = format!("{}{}.png", THUMB_CACHE, thumb.name);
+        if asset_path(&cache).exists() {
+            commands.entity(entity).insert(ImageNode::new(asset_server.load(cache)));
+        } else {
+            queue.pending.push_back((thumb.name.clone(), entity));
+        }
+    }
+}
+
+fn thumbnail_layers(
+    mut commands: Commands,
+    meshes: UnlayeredSpineMeshes,
+    thumb_spine_query: Query<(), With<ThumbnailSpine>>,
+) {
+    for (entity, spine) in meshes.iter() {
+        if thumb_spine_query.contains(spine) {
+            commands.entity(entity).insert(RenderLayers::layer(THUMB_LAYER));
+        }
+    }
+}
+
+fn render_thumbnails(
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+    mut spine_query: Query<AnySpine, (HasSpine, With<ThumbnailSpine>)>,
+    thumb_query: Query<(), With<SceneThumb>>,
+    mut skeleton_ready_msg: MessageReader<SkeletonReadyMsg>,
+    mut skeletons: SkeletonAssets,
+    mut images: ResMut<Assets<Image>>,
+    mut queue: ResMut<ThumbnailQueue>,
+    view_res: Res<ViewRes>,
+) {
+    if queue.job.is_none() {
+        while let Some((name, thumb)) = queue.pending.pop_front() {
+            // the list was rebuilt since the request
+            if !thumb_query.contains(thumb) {
+                continue;
+            }
+            let Some(file) = view_res.spines.get(&name) else { continue };
+            let mut spine = commands.spawn((
+                ThumbnailSpine,
+                Transform::default(),
+            ));
+            skeletons.insert(&mut spine, &asset_server, file);
+            let spine = spine.id();
+            let mut target = Image::new_fill(
+                Extent3d {
+                    width: THUMB_SIZE,
+                    height: THUMB_SIZE,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                &[0, 0, 0, 0],
+                TextureFormat::Rgba8UnormSrgb,
+                RenderAssetUsages::default(),
+            );
+            target.texture_descriptor.usage |=
+                TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC | TextureUsages::TEXTURE_BINDING;
+            queue.job = Some(ThumbnailJob {
+                name,
+                thumb,
+                spine,
+                camera: None,
+                target: images.add(target),
+                frames: 0,
+                readback: None,
+            });
+            break;
+        }
+    }
+    let Some(job) = queue.job.as_mut() else { return };
+
+    for msg in skeleton_ready_msg.read() {
+        if msg.entity != job.spine {
+            continue;
+        }
+        let Ok(spine) = spine_query.get_mut(msg.entity) else { continue };
+        // first frame of the default animation
+        let Some((x, y, width, height)) = with_spine!(spine, |spine| {
+            let (animation, x, y, width, height) = {
+                let data = spine.skeleton.data();
+                (data.animations().next().map(|a| a.name().to_string()), data.x(), data.y(), data.width(), data.height())
+            };
+            if let Some(animation) = animation {
+                let _ = spine.animation_state.set_animation_by_name(0, &animation, true);
+            }
+            spine.animation_state.set_timescale(0.);
+            (x, y, width, height)
+        }) else { continue };
+        // frame the setup pose bounds, skeletons exported without them get a full screen view
+        let (center, size) = if width > 0. && height > 0. {
+            (vec2(x + width / 2., y + height / 2.), width.max(height) * 1.05)
+        } else {
+            (Vec2::ZERO, VIEW_SIZE.y)
+        };
+        job.frames = 0;
+        job.camera = Some(commands.spawn((
+            Camera2d,
+            Camera {
+                order: -1,
+                clear_color: ClearColorConfig::Custom(Color::NONE),
+                ..default()
+            },
+            RenderTarget::Image(job.target.clone().into()),
+            RenderLayers::layer(THUMB_LAYER),
+            fixed_size_2d(size, size),
+            Transform::from_translation(center.extend(0.)),
+        )).id());
+    }
+
+    job.frames += 1;
+    if job.camera.is_none() && job.frames > THUMB_TIMEOUT {
+        warn!("thumbnail skeleton not ready: {}", job.name);
+        commands.entity(job.spine).despawn();
+        queue.job = None;
+    } else if let Some(readback) = job.readback {
+        if job.frames > THUMB_FRAMES + THUMB_TIMEOUT {
+            warn!("thumbnail readback timed out: {}", job.name);
+            commands.entity(readback).despawn();
+            commands.entity(job.spine).despawn();
+            if let Some(camera) = job.camera {
+                commands.entity(camera).despawn();
+            }
+            queue.job = None;
+        }
+    } else if job.camera.is_some() && job.frames >= THUMB_FRAMES {
+        job.readback = Some(commands.spawn(Readback::texture(job.target.clone())).observe(save_thumbnail).id());
+    }
+}
+
+fn save_thumbnail(
+    readback: On<ReadbackComplete>,
+    mut commands: Commands,
+    mut queue: ResMut<ThumbnailQueue>,
+) {
+    commands.entity(readback.entity).despawn();
+    let Some(job) = queue.job.take() else { return };
+    // the skeleton is blended onto a transparent target, the png wants straight alpha
+    let mut data = readback.data.clone();
+    unpremultiply(&mut data);
+    let image = Image::new(
+        Extent3d {
+            width: THUMB_SIZE,
+            height: THUMB_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::MAIN_WORLD,
+    );
+    let path = asset_path(format!("{}{}.png", THUMB_CACHE, job.name));
+    let saved = std::fs::create_dir_all(path.parent().unwrap())
+        .map_err(|e| e.to_string())
+        .and_then(|_| image.try_into_dynamic().map_err(|e| e.to_string()))
+        .and_then(|dynamic| dynamic.to_rgba8().save(&path).map_err(|e| e.to_string()));
+    if let Err(e) = saved {
+        warn!("failed to save thumbnail {}: {}", path.display(), e);
+    }
+    // the render target keeps the last frame, show it without reloading from disk
+    if let Ok(mut thumb) = commands.get_entity(job.thumb) {
+        thumb.insert(ImageNode::new(job.target));
+    }
+    commands.entity(job.spine).despawn();
+    if let Some(camera) = job.camera {
+        commands.entity(camera).despawn();
+    }
+}
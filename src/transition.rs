@@ -0,0 +1,289 @@
+use bevy::asset::{load_internal_asset, uuid_handle};
+use bevy::prelude::*;
+use bevy::reflect::TypePath;
+use bevy::render::render_resource::{AsBindGroup, ShaderType};
+use bevy::shader::ShaderRef;
+use bevy::sprite_render::{AlphaMode2d, Material2d, Material2dPlugin};
+use bevy::ui_render::{MaterialNode, UiMaterial, UiMaterialPlugin};
+
+use crate::camera::VIEW_SIZE;
+use crate::game::{RULE, VNGui, VNSpine, VNTexture, ViewRes, WaitEffect, Z_FADE, f32, should_wait, str};
+use crate::spine_facade::{AnySpine, HasSpine, with_spine};
+use crate::utage4;
+
+const RULE_SHADER: Handle<Shader> = uuid_handle!("6a1f0c52-3e7b-4d2a-9c41-7f2b8e5d0a13");
+const RULE_UI_SHADER: Handle<Shader> = uuid_handle!("c3d94e08-51a6-4b7f-8e20-0d6f3a9b1c74");
+
+#[derive(ShaderType, Clone, Copy, Debug)]
+struct RuleFadeParams {
+    color: LinearRgba,
+    progress: f32,
+    softness: f32,
+}
+
+/// Rule transition applied to a single sprite layer
+#[derive(Asset, TypePath, AsBindGroup, Clone)]
+struct RuleFadeMaterial {
+    #[uniform(0)]
+    params: RuleFadeParams,
+    #[texture(1)]
+    #[sampler(2)]
+    image: Handle<Image>,
+    #[texture(3)]
+    #[sampler(4)]
+    rule: Handle<Image>,
+}
+
+impl Material2d for RuleFadeMaterial {
+    fn fragment_shader() -> ShaderRef {
+        RULE_SHADER.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+}
+
+/// Rule transition applied to the whole screen
+#[derive(Asset, TypePath, AsBindGroup, Clone)]
+struct RuleFadeUiMaterial {
+    #[uniform(0)]
+    params: RuleFadeParams,
+    #[texture(1)]
+    #[sampler(2)]
+    rule: Handle<Image>,
+}
+
+impl UiMaterial for RuleFadeUiMaterial {
+    fn fragment_shader() -> ShaderRef {
+        RULE_UI_SHADER.into()
+    }
+}
+
+pub(crate) struct TransitionPlugin;
+
+impl Plugin for TransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            Material2dPlugin::<RuleFadeMaterial>::default(),
+            UiMaterialPlugin::<RuleFadeUiMaterial>::default(),
+        ))
+        .add_systems(Update, rule_fade);
+
+        load_internal_asset!(app, RULE_SHADER, "rule_fade.wgsl", Shader::from_wgsl);
+        load_internal_asset!(app, RULE_UI_SHADER, "rule_fade_ui.wgsl", Shader::from_wgsl);
+    }
+}
+
+enum RuleFadeState {
+    Pending,
+    Screen(Handle<RuleFadeUiMaterial>),
+    // material, original sprite color
+    Layer(Handle<RuleFadeMaterial>, Color),
+    // SpineRuleFade of the skeleton runtime, updated every frame
+    Spine,
+}
+
+/* A screen transition is a root ui node covering everything like FadeOverlay.
+A layer transition is spawned as a child of the VNTexture/VNSpine it applies to. */
+#[derive(Component)]
+pub(crate) struct RuleFade {
+    rule: Handle<Image>,
+    color: Color,
+    softness: f32,
+    timer: Timer,
+    fade_out: bool,
+    state: RuleFadeState,
+}
+
+impl RuleFade {
+    fn params(&self, color: Color, progress: f32) -> RuleFadeParams {
+        RuleFadeParams {
+            color: color.to_linear(),
+            progress,
+            softness: self.softness,
+        }
+    }
+}
+
+pub(crate) fn rule_fade_cmd(
+    f: &str,
+    node: &utage4::Node,
+    asset_server: &Res<AssetServer>,
+    commands: &mut Commands,
+    spine_query: &mut Query<(Entity, AnySpine, &mut VNSpine, &mut Transform), (Without<VNTexture>, Without<VNGui>)>,
+    tex_query: &mut Query<(Entity, &mut VNTexture, &mut Transform, &mut Sprite), (Without<VNSpine>, Without<VNGui>)>,
+    view_res: &ResMut<ViewRes>,
+) {
+    // Arg1 target, Arg2 rule texture, Arg3 vague, Arg4 screen color, Arg6 time
+    let rule_name = str!(node.arg2);
+    let rule = match view_res.vn.texture.get(rule_name).and_then(|t| t.file_name.as_deref()) {
+        Some(file_name) => format!("{}{}", *RULE, file_name),
+        None => format!("{}{}.png", *RULE, rule_name),
+    };
+    f32!(softness = node.arg3, 0.2);
+    f32!(time = node.arg6, 0.2);
+    let fade = || RuleFade {
+        rule: asset_server.load(rule.clone()),
+        color: Srgba::hex(str!(node.arg4, "#000000")).unwrap_or(Srgba::BLACK).into(),
+        softness,
+        timer: Timer::from_seconds(time, TimerMode::Once),
+        fade_out: matches!(f, "RuleFadeOut"),
+        state: RuleFadeState::Pending,
+    };
+    let wait = should_wait(&node.wait_type);
+    match node.arg1.as_deref() {
+        None | Some("Camera") => {
+            let mut cmd = commands.spawn((
+                Node {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    ..default()
+                },
+                ZIndex(Z_FADE),
+                fade(),
+            ));
+            if wait {
+                cmd.insert(WaitEffect);
+            }
+        }
+        // match label name or layer name
+        Some(l) => {
+            let targets = tex_query.iter()
+                .filter(|x| x.1.1 == l || x.1.2 == l)
+                .map(|x| x.0)
+                .chain(spine_query.iter().filter(|x| x.2.0 == l || x.2.2 == l).map(|x| x.0))
+                .collect::<Vec<_>>();
+            if targets.is_empty() {
+                warn!("{} target not found: {}", f, l);
+            }
+            for target in targets {
+                info!("{} on {}", f, l);
+                commands.entity(target).with_children(|parent| {
+                    let mut cmd = parent.spawn((
+                        Transform::from_xyz(0., 0., 0.001),
+                        fade(),
+                    ));
+                    if wait {
+                        cmd.insert(WaitEffect);
+                    }
+                });
+            }
+        }
+    }
+}
+
+fn rule_fade(
+    mut commands: Commands,
+    mut fade_query: Query<(Entity, &mut RuleFade, Option<&ChildOf>, Option<&mut Transform>)>,
+    mut sprite_query: Query<&mut Sprite, With<VNTexture>>,
+    spine_query: Query<(AnySpine, &GlobalTransform), (HasSpine, With<VNSpine>)>,
+    mut materials: ResMut<Assets<RuleFadeMaterial>>,
+    mut ui_materials: ResMut<Assets<RuleFadeUiMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    images: Res<Assets<Image>>,
+    time: Res<Time>,
+) {
+    for (entity, mut fade, parent, transform) in fade_query.iter_mut() {
+        let parent = parent.map(ChildOf::parent);
+        if matches!(fade.state, RuleFadeState::Pending) {
+            fade.state = match parent {
+                None => {
+                    let handle = ui_materials.add(RuleFadeUiMaterial {
+                        params: fade.params(fade.color, if fade.fade_out { 0. } else { 1. }),
+                        rule: fade.rule.clone(),
+                    });
+                    commands.entity(entity).insert(MaterialNode(handle.clone()));
+                    RuleFadeState::Screen(handle)
+                }
+                Some(p) => if let Ok(mut sprite) = sprite_query.get_mut(p) {
+                    let color = sprite.color;
+                    let handle = materials.add(RuleFadeMaterial {
+                        params: fade.params(color, if fade.fade_out { 1. } else { 0. }),
+                        image: sprite.image.clone(),
+                        rule: fade.rule.clone(),
+                    });
+                    commands.entity(entity).insert((
+                        Mesh2d(meshes.add(Rectangle::new(1., 1.))),
+                        MeshMaterial2d(handle.clone()),
+                    ));
+                    // the material draws the layer during the transition
+                    sprite.color.set_alpha(0.);
+                    RuleFadeState::Layer(handle, color)
+                } else if spine_query.contains(p) {
+                    RuleFadeState::Spine
+                } else {
+                    commands.entity(entity).despawn();
+                    continue;
+                }
+            };
+        }
+
+        fade.timer.tick(time.delta());
+        // fade in reveals a layer but uncovers the screen
+        let reveal = if fade.fade_out { fade.timer.fraction_remaining() } else { fade.timer.fraction() };
+        let finished = fade.timer.is_finished();
+        match &fade.state {
+            RuleFadeState::Pending => {}
+            RuleFadeState::Screen(handle) => {
+                if finished {
+                    info!("rule fade{} effect finished", if fade.fade_out {"out"} else {"in"});
+                    commands.entity(entity).despawn();
+                } else if let Some(mut material) = ui_materials.get_mut(handle) {
+                    material.params = fade.params(fade.color, 1. - reveal);
+                }
+            }
+            RuleFadeState::Layer(handle, color) => {
+                let Some(p) = parent else { continue };
+                if finished {
+                    if let Ok(mut sprite) = sprite_query.get_mut(p) {
+                        sprite.color = *color;
+                    }
+                    if fade.fade_out {
+                        commands.entity(p).insert(Visibility::Hidden);
+                    }
+                    commands.entity(entity).despawn();
+                    continue;
+                }
+                if let Some(mut material) = materials.get_mut(handle) {
+                    material.params = fade.params(*color, reveal);
+                    if let (Some(image), Some(mut transform)) = (images.get(&material.image), transform) {
+                        transform.scale = image.size_f32().extend(1.);
+                    }
+                }
+            }
+            RuleFadeState::Spine => {
+                let Some(p) = parent else { continue };
+                let Ok((spine, transform)) = spine_query.get(p) else { continue };
+                if finished {
+                    with_spine!(spine, |_spine, runtime| {
+                        commands.entity(p).remove::<runtime::SpineRuleFade>();
+                    });
+                    if fade.fade_out {
+                        commands.entity(p).insert(Visibility::Hidden);
+                    }
+                    commands.entity(entity).despawn();
+                    continue;
+                }
+                // the rule image covers the setup pose bounds, as it covers the image of a sprite layer
+                with_spine!(spine, |spine, runtime| {
+                    let data = spine.skeleton.data();
+                    let (min, max) = if data.width() > 0. && data.height() > 0. {
+                        (vec2(data.x(), data.y()), vec2(data.x() + data.width(), data.y() + data.height()))
+                    } else {
+                        (-VIEW_SIZE / 2., VIEW_SIZE / 2.)
+                    };
+                    commands.entity(p).insert(runtime::SpineRuleFade {
+                        rule: fade.rule.clone(),
+                        rect: Rect::from_corners(
+                            transform.transform_point(min.extend(0.)).truncate(),
+                            transform.transform_point(max.extend(0.)).truncate(),
+                        ),
+                        progress: reveal,
+                        softness: fade.softness,
+                    });
+                });
+            }
+        }
+    }
+}
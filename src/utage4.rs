@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Localized text columns of a scenario sheet, next to the default `Text`
+pub const LANGUAGES: [&str; 5] = ["Japanese", "English", "ChineseSimplified", "ChineseTraditional", "Korean"];
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 struct Row {
@@ -120,7 +123,7 @@ pub struct TextureEntry {
     pub cg_categolly: Option<String>,
 }
 
-#[derive(Debug, Default, Serialize)]
+#[derive(Clone, Debug, Default, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Node {
     pub command: Option<String>,
@@ -135,6 +138,15 @@ pub struct Node {
     pub page_ctrl: Option<String>,
     pub voice: Option<String>,
     pub window_type: Option<String>,
+    // text per LANGUAGES column
+    pub translations: HashMap<String, String>,
+}
+
+impl Node {
+    /// Text in `language`, the default column when it has no translation
+    pub fn text_in(&self, language: Option<&str>) -> Option<&String> {
+        language.and_then(|l| self.translations.get(l)).or(self.text.as_ref())
+    }
 }
 
 fn row_to_map<'a>(row: &'a Row, headers: &'a [String]) -> HashMap<&'a str, &'a str> {
@@ -360,6 +372,9 @@ pub fn parse_book(content: String) -> Vec<Node> {
                     page_ctrl: map.get("PageCtrl").map(|s| s.to_string()),
                     voice: map.get("Voice").map(|s| s.to_string()),
                     window_type: map.get("WindowType").map(|s| s.to_string()),
+                    translations: LANGUAGES.iter()
+                        .filter_map(|l| map.get(l).map(|s| (l.to_string(), s.to_string())))
+                        .collect(),
                 };
                 nodes.push(node);
             }